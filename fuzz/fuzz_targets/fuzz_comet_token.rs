@@ -3,15 +3,13 @@
 use libfuzzer_sys::{fuzz_target, Corpus};
 use soroban_sdk::testutils::Address as _;
 use soroban_sdk::token;
-use soroban_sdk::{
-    unwrap::UnwrapOptimized, Address, Env, Error, InvokeError, String, TryFromVal, Val,
-};
+use soroban_sdk::{unwrap::UnwrapOptimized, Address, Env, Error, String};
 use soroban_token_fuzzer::*;
 
 use comet::c_pool::comet::{CometPoolContract, CometPoolContractClient};
 
 use soroban_sdk::testutils::arbitrary::fuzz_catch_panic;
-use soroban_sdk::xdr::{ScErrorCode, ScErrorType};
+use soroban_sdk::xdr::{ScErrorCode, ScErrorType, SorobanAuthorizationEntry};
 
 // This is the entrypoint.
 //
@@ -43,7 +41,14 @@ impl ContractTokenOps for TokenOps {
     /// contract-specific one-time initialization.
     ///
     /// This function will be called once.
-    fn register_contract_init(&self, env: &Env, admin: &Address) -> Address {
+    ///
+    /// The pool's LP token has fixed metadata, so `_params` are ignored.
+    fn register_contract_init(
+        &self,
+        env: &Env,
+        admin: &Address,
+        _params: &InitParams,
+    ) -> Option<Address> {
         let token_contract_id = env.register_contract(None, CometPoolContract);
 
         let admin_client = CometPoolContractClient::new(env, &token_contract_id);
@@ -80,7 +85,7 @@ impl ContractTokenOps for TokenOps {
         admin_client.set_swap_fee(&3_000, &controller);
         admin_client.finalize();
 
-        token_contract_id
+        Some(token_contract_id)
     }
 
     /// Register the contract with the environment.
@@ -127,6 +132,17 @@ impl ContractTokenOps for TokenOps {
         let token_2_client = token::Client::new(&env, &addr2);
         token_2_client.allowance(&Address::generate(&env), &Address::generate(&env));
     }
+
+    /// Comet mints LP tokens by joining the pool,
+    /// which doesn't emit a standard `mint` event.
+    fn mint_emits_standard_event(&self) -> bool {
+        false
+    }
+
+    /// Anybody can mint LP tokens by joining the pool.
+    fn mint_requires_admin_auth(&self) -> bool {
+        false
+    }
 }
 
 impl<'a> AdminClient<'a> {
@@ -142,11 +158,7 @@ impl<'a> AdminClient<'a> {
 }
 
 impl<'a> TokenAdminClient<'a> for AdminClient<'a> {
-    fn try_mint(
-        &self,
-        to: &Address,
-        amount: &i128,
-    ) -> Result<Result<(), <() as TryFromVal<Env, Val>>::Error>, Result<Error, InvokeError>> {
+    fn try_mint(&self, to: &Address, amount: &i128) -> UnitResult {
         self.client.env.mock_all_auths();
 
         let (token_address_1, token_address_2) = self.get_token_addresses();
@@ -173,6 +185,17 @@ impl<'a> TokenAdminClient<'a> for AdminClient<'a> {
             Ok(Ok(()))
         }
     }
+
+    /// Minting by joining the pool needs no admin auth,
+    /// and `try_mint` mocks all auths itself, so `_auths` are ignored.
+    fn set_auths<'b>(
+        &self,
+        _auths: &'b [SorobanAuthorizationEntry],
+    ) -> Box<dyn TokenAdminClient<'b> + 'b> {
+        Box::new(AdminClient {
+            client: CometPoolContractClient::new(&self.client.env, &self.client.address),
+        })
+    }
 }
 
 fn create_and_init_token_contract<'a>(
@@ -1,7 +1,7 @@
 #![no_main]
 
 use libfuzzer_sys::{fuzz_target, Corpus};
-use soroban_sdk::{Address, Env, Error, InvokeError, String, TryFromVal, Val};
+use soroban_sdk::String;
 use soroban_token_fuzzer::*;
 
 // This is the entrypoint.
@@ -14,69 +14,37 @@ use soroban_token_fuzzer::*;
 // instruct the fuzzer that the `Input` case was unusable
 // (for various reasons).
 fuzz_target!(|input: Input| -> Corpus {
-    // Each token needs to construct its own `Config` by passing
-    // to `contract` a type that implements `ContractTokenOps`.
-    let config = Config::contract(TokenOps);
+    // This token implements the admin functions of the
+    // `StellarAssetInterface`, so it can use the
+    // `stellar_asset_interface` constructor instead of
+    // implementing `ContractTokenOps` itself.
+    let config = Config::stellar_asset_interface(
+        // Register the contract with the environment,
+        // at the given address on every transaction after the first.
+        |env, token_contract_id| {
+            env.register_contract(token_contract_id, example_token::contract::Token)
+        },
+        // Call the token's initialization function with the fuzzed parameters.
+        // It is called once to initialize the token,
+        // and then again, when it must fail.
+        |env, token_contract_id, admin, params| {
+            let admin_client = example_token::TokenClient::new(env, token_contract_id);
+            admin_client.try_initialize(
+                admin,
+                &params.decimals,
+                &String::from_str(env, &params.name),
+                &String::from_str(env, &params.symbol),
+            )
+        },
+    )
+    // Upgrade the token between transactions. The example token
+    // has no second version, so it is replaced with itself,
+    // which still checks that its state survives reregistration.
+    // A token with a new version would register that here instead,
+    // or pass its WASM to `upgrade_contract_wasm`.
+    .with_upgrade(|env, token_contract_id| {
+        env.register_contract(token_contract_id, example_token::contract::Token);
+    });
     // Run the fuzzer.
     fuzz_token(config, input)
 });
-
-// Implements `ContractTokenOps`
-struct TokenOps;
-
-// Implements `TokenAdminClient`
-struct AdminClient<'a> {
-    client: example_token::TokenClient<'a>,
-}
-
-impl ContractTokenOps for TokenOps {
-    /// Register the contract with the environment and perform
-    /// contract-specific one-time initialization.
-    ///
-    /// This function will be called once.
-    fn register_contract_init(&self, env: &Env, admin: &Address) -> Address {
-        let token_contract_id = env.register_contract(None, example_token::contract::Token);
-
-        let admin_client = example_token::TokenClient::new(&env, &token_contract_id);
-        let r = admin_client.try_initialize(
-            &admin,
-            &10,
-            &String::from_str(&env, "token"),
-            &String::from_str(&env, "TKN"),
-        );
-
-        assert!(r.is_ok());
-
-        token_contract_id
-    }
-
-    /// Register the contract with the environment.
-    ///
-    /// This will be called on all subsequent transactions
-    /// after the first, i.e. every time time is advanced
-    /// and the `Env` is recreated.
-    fn reregister_contract(&self, env: &Env, token_contract_id: &Address) {
-        env.register_contract(Some(token_contract_id), example_token::contract::Token);
-    }
-
-    /// Create an admin client.
-    fn new_admin_client<'a>(
-        &self,
-        env: &Env,
-        token_contract_id: &Address,
-    ) -> Box<dyn TokenAdminClient<'a> + 'a> {
-        Box::new(AdminClient {
-            client: example_token::TokenClient::new(&env, &token_contract_id),
-        })
-    }
-}
-
-impl<'a> TokenAdminClient<'a> for AdminClient<'a> {
-    fn try_mint(
-        &self,
-        to: &Address,
-        amount: &i128,
-    ) -> Result<Result<(), <() as TryFromVal<Env, Val>>::Error>, Result<Error, InvokeError>> {
-        self.client.try_mint(to, amount)
-    }
-}
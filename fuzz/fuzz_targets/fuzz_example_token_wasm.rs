@@ -0,0 +1,36 @@
+#![no_main]
+
+use libfuzzer_sys::{fuzz_target, Corpus};
+use soroban_sdk::String;
+use soroban_token_fuzzer::*;
+
+// Fuzz the example token as deployed, compiled to WASM, built with
+//
+//     cargo build --manifest-path tokens/example-token/Cargo.toml \
+//         --target wasm32-unknown-unknown --release
+//
+// This catches bugs that only show up in the release build,
+// e.g. from `panic = "abort"`, that `fuzz_example_token` misses.
+const EXAMPLE_TOKEN_WASM: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../tokens/example-token/target/wasm32-unknown-unknown/release/example_token.wasm"
+);
+
+fuzz_target!(|input: Input| -> Corpus {
+    // Like `fuzz_example_token`, but the token is registered
+    // from its WASM instead of its Rust code.
+    // The WASM is only read the first time.
+    let config = Config::stellar_asset_interface_wasm(
+        EXAMPLE_TOKEN_WASM,
+        |env, token_contract_id, admin, params| {
+            let admin_client = example_token::TokenClient::new(env, token_contract_id);
+            admin_client.try_initialize(
+                admin,
+                &params.decimals,
+                &String::from_str(env, &params.name),
+                &String::from_str(env, &params.symbol),
+            )
+        },
+    );
+    fuzz_token(config, input)
+});
@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::{fuzz_target, Corpus};
+use soroban_token_fuzzer::*;
+
+fuzz_target!(|input: Input| -> Corpus {
+    let config = Config::native_lumens();
+    fuzz_token(config, input)
+});
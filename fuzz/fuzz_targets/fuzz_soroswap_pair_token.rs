@@ -1,7 +1,8 @@
 #![no_main]
 
 use libfuzzer_sys::{fuzz_target, Corpus};
-use soroban_sdk::{Address, Env, Error, InvokeError, String, TryFromVal, Val};
+use soroban_sdk::xdr::SorobanAuthorizationEntry;
+use soroban_sdk::{Address, Env, String};
 use soroban_token_fuzzer::*;
 
 use soroswap_pair_token::soroswap_pair_token::{SoroswapPairToken, SoroswapPairTokenClient};
@@ -36,20 +37,41 @@ impl ContractTokenOps for TokenOps {
     /// contract-specific one-time initialization.
     ///
     /// This function will be called once.
-    fn register_contract_init(&self, env: &Env, admin: &Address) -> Address {
+    fn register_contract_init(
+        &self,
+        env: &Env,
+        admin: &Address,
+        params: &InitParams,
+    ) -> Option<Address> {
         let token_contract_id = env.register_contract(None, SoroswapPairToken);
 
-        let admin_client = SoroswapPairTokenClient::new(&env, &token_contract_id);
+        let admin_client = SoroswapPairTokenClient::new(env, &token_contract_id);
         let r = admin_client.try_initialize(
-            &admin,
-            &10,
-            &String::from_str(&env, "token"),
-            &String::from_str(&env, "TKN"),
+            admin,
+            &params.decimals,
+            &String::from_str(env, &params.name),
+            &String::from_str(env, &params.symbol),
         );
 
-        assert!(r.is_ok());
+        r.is_ok().then_some(token_contract_id)
+    }
 
-        token_contract_id
+    /// Call the token's initialization function again,
+    /// which must fail.
+    fn try_reinitialize(
+        &self,
+        env: &Env,
+        token_contract_id: &Address,
+        admin: &Address,
+        params: &InitParams,
+    ) -> Option<UnitResult> {
+        let admin_client = SoroswapPairTokenClient::new(env, token_contract_id);
+        Some(admin_client.try_initialize(
+            admin,
+            &params.decimals,
+            &String::from_str(env, &params.name),
+            &String::from_str(env, &params.symbol),
+        ))
     }
 
     /// Register the contract with the environment.
@@ -74,11 +96,17 @@ impl ContractTokenOps for TokenOps {
 }
 
 impl<'a> TokenAdminClient<'a> for AdminClient<'a> {
-    fn try_mint(
-        &self,
-        to: &Address,
-        amount: &i128,
-    ) -> Result<Result<(), <() as TryFromVal<Env, Val>>::Error>, Result<Error, InvokeError>> {
+    fn try_mint(&self, to: &Address, amount: &i128) -> UnitResult {
         self.client.try_mint(to, amount)
     }
+
+    fn set_auths<'b>(
+        &self,
+        auths: &'b [SorobanAuthorizationEntry],
+    ) -> Box<dyn TokenAdminClient<'b> + 'b> {
+        Box::new(AdminClient {
+            client: SoroswapPairTokenClient::new(&self.client.env, &self.client.address)
+                .set_auths(auths),
+        })
+    }
 }
@@ -0,0 +1,22 @@
+#![no_main]
+
+use libfuzzer_sys::{fuzz_target, Corpus};
+use soroban_token_fuzzer::*;
+use std::sync::OnceLock;
+
+// Fuzz any token compiled to WASM, without writing any Rust, e.g.
+//
+//     TOKEN_WASM=path/to/my_token.wasm cargo +nightly fuzz run fuzz_wasm_token
+//
+// The token's initialization and mint functions are declared
+// with `TOKEN_INIT` and `TOKEN_MINT`, or in a file named by `TOKEN_DECLARATION`.
+// See `TokenDeclaration::from_env`.
+static DECLARATION: OnceLock<TokenDeclaration> = OnceLock::new();
+
+fuzz_target!(|input: Input| -> Corpus {
+    // Reading the WASM and checking it against its contract spec
+    // only needs to be done once.
+    let declaration = DECLARATION.get_or_init(TokenDeclaration::from_env);
+    let config = Config::declared(declaration.clone());
+    fuzz_token(config, input)
+});
@@ -0,0 +1,212 @@
+//! Custom account contracts used as the authorizers of contract addresses.
+//!
+//! These are simplified versions of the account contracts in
+//! [soroban-examples](https://github.com/stellar/soroban-examples),
+//! and actually verify signatures, so that authorization
+//! by contract addresses is exercised as thoroughly as
+//! authorization by Stellar accounts.
+
+use ed25519_dalek::{Signer, SigningKey};
+use soroban_sdk::auth::{Context, CustomAccountInterface};
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, Address, BytesN, Env, TryFromVal, Vec,
+};
+
+// Nothing reads these contracts' storage between transactions,
+// so their instances need to be kept alive explicitly.
+const INSTANCE_TTL_THRESHOLD: u32 = crate::DAY_IN_LEDGERS;
+const INSTANCE_TTL_EXTEND_TO: u32 = crate::DAY_IN_LEDGERS * 30;
+
+/// A signature in the format expected by Stellar accounts
+/// and by [`MultisigAccountContract`].
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct AccountEd25519Signature {
+    pub public_key: BytesN<32>,
+    pub signature: BytesN<64>,
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum AccountError {
+    UnknownSigner = 1,
+    NotEnoughSignatures = 2,
+    BadSignatureOrder = 3,
+    Rejected = 4,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    Signers,
+    Threshold,
+}
+
+// Each contract is in its own module since they all define `__check_auth`.
+mod ed25519 {
+    use super::*;
+
+    /// An account controlled by a single ed25519 key.
+    #[contract]
+    pub struct Ed25519AccountContract;
+
+    #[contractimpl]
+    impl CustomAccountInterface for Ed25519AccountContract {
+        type Signature = BytesN<64>;
+        type Error = AccountError;
+
+        #[allow(non_snake_case)]
+        fn __check_auth(
+            env: Env,
+            signature_payload: BytesN<32>,
+            signature: BytesN<64>,
+            _auth_contexts: Vec<Context>,
+        ) -> Result<(), AccountError> {
+            let public_key: BytesN<32> = env.storage().instance().get(&DataKey::Signers).unwrap();
+            env.crypto()
+                .ed25519_verify(&public_key, &signature_payload.into(), &signature);
+            Ok(())
+        }
+    }
+}
+
+mod multisig {
+    use super::*;
+
+    /// An account controlled by a set of ed25519 keys,
+    /// requiring signatures from at least a threshold number of them.
+    ///
+    /// Signatures must be ordered by public key, without duplicates.
+    #[contract]
+    pub struct MultisigAccountContract;
+
+    #[contractimpl]
+    impl CustomAccountInterface for MultisigAccountContract {
+        type Signature = Vec<AccountEd25519Signature>;
+        type Error = AccountError;
+
+        #[allow(non_snake_case)]
+        fn __check_auth(
+            env: Env,
+            signature_payload: BytesN<32>,
+            signatures: Vec<AccountEd25519Signature>,
+            _auth_contexts: Vec<Context>,
+        ) -> Result<(), AccountError> {
+            let signers: Vec<BytesN<32>> = env.storage().instance().get(&DataKey::Signers).unwrap();
+            let threshold: u32 = env.storage().instance().get(&DataKey::Threshold).unwrap();
+
+            if signatures.len() < threshold {
+                return Err(AccountError::NotEnoughSignatures);
+            }
+
+            let mut prev_public_key: Option<BytesN<32>> = None;
+            for signature in signatures.iter() {
+                if let Some(prev_public_key) = prev_public_key {
+                    if prev_public_key >= signature.public_key {
+                        return Err(AccountError::BadSignatureOrder);
+                    }
+                }
+                if !signers.contains(&signature.public_key) {
+                    return Err(AccountError::UnknownSigner);
+                }
+                env.crypto().ed25519_verify(
+                    &signature.public_key,
+                    &signature_payload.clone().into(),
+                    &signature.signature,
+                );
+                prev_public_key = Some(signature.public_key);
+            }
+
+            Ok(())
+        }
+    }
+}
+
+mod rejecting {
+    use super::*;
+
+    /// An account that never authorizes anything.
+    #[contract]
+    pub struct RejectingAccountContract;
+
+    #[contractimpl]
+    impl CustomAccountInterface for RejectingAccountContract {
+        type Signature = ();
+        type Error = AccountError;
+
+        #[allow(non_snake_case)]
+        fn __check_auth(
+            _env: Env,
+            _signature_payload: BytesN<32>,
+            _signatures: (),
+            _auth_contexts: Vec<Context>,
+        ) -> Result<(), AccountError> {
+            Err(AccountError::Rejected)
+        }
+    }
+}
+
+pub use ed25519::Ed25519AccountContract;
+pub use multisig::MultisigAccountContract;
+pub use rejecting::RejectingAccountContract;
+
+/// Register an [`Ed25519AccountContract`] at `address`, controlled by `key`.
+///
+/// This must be done again every time the `Env` is recreated.
+pub fn register_ed25519_account(env: &Env, address: &Address, key: &SigningKey) {
+    env.register_contract(address, Ed25519AccountContract);
+    env.as_contract(address, || {
+        let storage = env.storage().instance();
+        storage.set(&DataKey::Signers, &public_key(env, key));
+        storage.extend_ttl(INSTANCE_TTL_THRESHOLD, INSTANCE_TTL_EXTEND_TO);
+    });
+}
+
+/// Register a [`MultisigAccountContract`] at `address`.
+///
+/// This must be done again every time the `Env` is recreated.
+pub fn register_multisig_account(
+    env: &Env,
+    address: &Address,
+    keys: &[SigningKey],
+    threshold: u32,
+) {
+    env.register_contract(address, MultisigAccountContract);
+    env.as_contract(address, || {
+        let mut signers = Vec::new(env);
+        for key in keys {
+            signers.push_back(public_key(env, key));
+        }
+        let storage = env.storage().instance();
+        storage.set(&DataKey::Signers, &signers);
+        storage.set(&DataKey::Threshold, &threshold);
+        storage.extend_ttl(INSTANCE_TTL_THRESHOLD, INSTANCE_TTL_EXTEND_TO);
+    });
+}
+
+/// Register a [`RejectingAccountContract`] at `address`.
+///
+/// This must be done again every time the `Env` is recreated.
+pub fn register_rejecting_account(env: &Env, address: &Address) {
+    env.register_contract(address, RejectingAccountContract);
+}
+
+pub fn public_key(env: &Env, key: &SigningKey) -> BytesN<32> {
+    BytesN::<32>::try_from_val(env, &key.verifying_key().to_bytes()).unwrap()
+}
+
+pub fn sign_payload(env: &Env, key: &SigningKey, payload: &[u8]) -> BytesN<64> {
+    BytesN::<64>::try_from_val(env, &key.sign(payload).to_bytes()).unwrap()
+}
+
+pub fn sign_payload_for_account(
+    env: &Env,
+    key: &SigningKey,
+    payload: &[u8],
+) -> AccountEd25519Signature {
+    AccountEd25519Signature {
+        public_key: public_key(env, key),
+        signature: sign_payload(env, key, payload),
+    }
+}
@@ -1,12 +1,15 @@
-use crate::input::NUMBER_OF_ADDRESSES;
+use crate::accounts;
+use crate::input::{MAX_NUMBER_OF_ADDRESSES, MIN_NUMBER_OF_ADDRESSES};
 use arbitrary::Unstructured;
 use ed25519_dalek::SigningKey;
 use soroban_sdk::testutils::arbitrary::arbitrary;
+use soroban_sdk::token::{self, StellarAssetClient};
 use soroban_sdk::xdr::{
-    AccountEntry, AccountEntryExt, AccountId, AlphaNum4, AssetCode4, Hash, LedgerEntry,
-    LedgerEntryData, LedgerEntryExt, LedgerKey, LedgerKeyAccount, LedgerKeyTrustLine, PublicKey,
-    ScAddress, SequenceNumber, Signer, SignerKey, Thresholds, TrustLineAsset, TrustLineEntry,
-    TrustLineEntryExt, TrustLineFlags, Uint256,
+    AccountEntry, AccountEntryExt, AccountFlags, AccountId, AlphaNum12, AlphaNum4, Asset,
+    AssetCode12, AssetCode4, ContractExecutable, ContractIdPreimage, CreateContractArgs, Hash,
+    HostFunction, LedgerEntry, LedgerEntryData, LedgerEntryExt, LedgerKey, LedgerKeyAccount,
+    LedgerKeyTrustLine, PublicKey, ScAddress, ScVal, SequenceNumber, Signer, SignerKey, Thresholds,
+    TrustLineAsset, TrustLineEntry, TrustLineEntryExt, TrustLineFlags, Uint256,
 };
 use soroban_sdk::{Address, Env, TryFromVal};
 use std::rc::Rc;
@@ -21,35 +24,148 @@ pub struct AddressGenerator {
     // for different purposes.
     //
     // fixme use a different address generation algorithm to avoid this collision.
-    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(u64::MIN + 10..=u64::MAX - NUMBER_OF_ADDRESSES as u64))]
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(u64::MIN + 10..=u64::MAX - MAX_NUMBER_OF_ADDRESSES as u64))]
     pub address_seed: u64,
-    pub address_types: [AddressType; NUMBER_OF_ADDRESSES],
+    /// How many addresses to generate.
+    ///
+    /// This may be overridden by [`Config::with_number_of_addresses`](crate::config::Config::with_number_of_addresses).
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(MIN_NUMBER_OF_ADDRESSES..=MAX_NUMBER_OF_ADDRESSES))]
+    pub number_of_addresses: usize,
+    /// The types of each address, of which only the first
+    /// `number_of_addresses` are used.
+    pub address_types: [AddressType; MAX_NUMBER_OF_ADDRESSES],
 }
 
 #[derive(Clone, Debug, arbitrary::Arbitrary)]
 pub enum AddressType {
-    Account,
-    Contract,
+    /// A Stellar account.
+    Account(ClassicAccount),
+    /// A contract account controlled by a single ed25519 key.
+    Ed25519Contract,
+    /// A contract account requiring `threshold` of
+    /// [`MULTISIG_SIGNERS`] ed25519 signatures.
+    MultisigContract {
+        #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(1..=MULTISIG_SIGNERS as u32))]
+        threshold: u32,
+    },
+    /// A contract account that never authorizes anything.
+    RejectingContract,
 }
 
+/// The classic ledger state of a Stellar account.
+#[derive(Clone, Debug, arbitrary::Arbitrary)]
+pub struct ClassicAccount {
+    /// Whether the account entry exists.
+    ///
+    /// Accounts that don't exist can't authorize anything.
+    #[arbitrary(with = |u: &mut Unstructured| u.ratio(9, 10))]
+    pub exists: bool,
+    /// The account's trustline to the asset of the Stellar Asset Contract.
+    ///
+    /// This is ignored if the account doesn't exist;
+    /// use [`ClassicAccount::trustline`].
+    #[arbitrary(with = |u: &mut Unstructured| {
+        if u.ratio(9, 10)? { Ok(Some(u.arbitrary()?)) } else { Ok(None) }
+    })]
+    pub trustline: Option<Trustline>,
+    /// The account's balance of lumens, in stroops.
+    ///
+    /// This is never less than the minimum balance,
+    /// and is sometimes close enough to `i64::MAX` to be exceeded.
+    #[arbitrary(with = |u: &mut Unstructured| {
+        if u.ratio(1, 2)? {
+            u.int_in_range(MIN_ACCOUNT_BALANCE..=MIN_ACCOUNT_BALANCE + 1000)
+        } else if u.ratio(1, 2)? {
+            u.int_in_range(i64::MAX - 1000..=i64::MAX)
+        } else {
+            u.int_in_range(MIN_ACCOUNT_BALANCE..=i64::MAX)
+        }
+    })]
+    pub balance: i64,
+}
+
+#[derive(Clone, Debug, arbitrary::Arbitrary)]
+pub struct Trustline {
+    #[arbitrary(with = |u: &mut Unstructured| u.ratio(9, 10))]
+    pub authorized: bool,
+    pub authorized_to_maintain_liabilities: bool,
+    #[arbitrary(with = |u: &mut Unstructured| u.ratio(9, 10))]
+    pub clawback_enabled: bool,
+    #[arbitrary(with = |u: &mut Unstructured| {
+        // mostly unlimited, sometimes low enough to be reached
+        if u.ratio(3, 4)? {
+            Ok(i64::MAX)
+        } else if u.ratio(1, 2)? {
+            u.int_in_range(0..=1000)
+        } else {
+            u.int_in_range(0..=i64::MAX)
+        }
+    })]
+    pub limit: i64,
+}
+
+/// The classic asset wrapped by the Stellar Asset Contract.
+#[derive(Clone, Debug, arbitrary::Arbitrary)]
+pub struct ClassicAsset {
+    /// The asset code, without padding:
+    /// 1 to 4 alphanumeric characters for an `AlphaNum4` asset,
+    /// or 5 to 12 for an `AlphaNum12` asset.
+    #[arbitrary(with = arbitrary_asset_code)]
+    pub code: RustVec<u8>,
+    /// The ed25519 public key of the issuer.
+    pub issuer: [u8; 32],
+}
+
+fn arbitrary_asset_code(u: &mut Unstructured) -> arbitrary::Result<RustVec<u8>> {
+    const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+    let len = u.int_in_range(1..=12)?;
+    (0..len).map(|_| u.choose(CHARS).copied()).collect()
+}
+
+impl ClassicAccount {
+    /// The account's trustline, if it exists.
+    pub fn trustline(&self) -> Option<&Trustline> {
+        self.trustline.as_ref().filter(|_| self.exists)
+    }
+}
+
+/// The base reserve of the ledger, in stroops, as on the Stellar network.
+///
+/// This only matters to the native lumens token,
+/// which won't let an account's balance fall below its minimum balance.
+pub const BASE_RESERVE: u32 = 5_000_000;
+
+/// The minimum balance of the generated accounts,
+/// which have no subentries.
+pub const MIN_ACCOUNT_BALANCE: i64 = 2 * BASE_RESERVE as i64;
+
+/// The number of keys controlling each multisig contract account.
+pub const MULTISIG_SIGNERS: usize = 3;
+
 pub struct TestSigner {
     pub address: Address,
-    pub key: Option<SigningKey>,
+    pub kind: SignerKind,
+}
+
+pub enum SignerKind {
+    Account {
+        key: SigningKey,
+        state: ClassicAccount,
+    },
+    Ed25519Contract(SigningKey),
+    MultisigContract {
+        keys: RustVec<SigningKey>,
+        threshold: u32,
+    },
+    RejectingContract,
 }
 
 impl AddressGenerator {
     pub fn generate_signers(&self, env: &Env) -> RustVec<TestSigner> {
-        self.generate_signers_with_bytes(env)
-            .into_iter()
-            .map(|(a, _)| a)
-            .collect()
-    }
-
-    fn generate_signers_with_bytes(&self, env: &Env) -> RustVec<(TestSigner, [u8; 32])> {
-        let mut signers = RustVec::<(TestSigner, [u8; 32])>::new();
+        let mut signers = RustVec::<TestSigner>::new();
 
         // fixme seed of 0 or 1 seems to generate bogus contract addresses
-        for i in 0..NUMBER_OF_ADDRESSES {
+        for i in 0..self.number_of_addresses {
             let seed = self
                 .address_seed
                 .checked_add(i as u64)
@@ -60,8 +176,11 @@ impl AddressGenerator {
                 seed[1], seed[2], seed[3], seed[4], seed[5], seed[6], seed[7],
             ];
 
-            let test_signer = match self.address_types[i] {
-                AddressType::Account => {
+            let contract_address =
+                || Address::try_from_val(env, &ScAddress::Contract(Hash(signer_bytes))).unwrap();
+
+            let test_signer = match &self.address_types[i] {
+                AddressType::Account(state) => {
                     let signing_key = SigningKey::from_bytes(&signer_bytes);
                     let verifying_key = signing_key.verifying_key().to_bytes();
 
@@ -69,46 +188,161 @@ impl AddressGenerator {
                         AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(verifying_key)));
                     let sc_address = ScAddress::Account(account_id);
                     let address = Address::try_from_val(env, &sc_address).unwrap();
-                    let test_signer = TestSigner {
-                        address,
-                        key: Some(signing_key),
-                    };
 
-                    test_signer
+                    TestSigner {
+                        address,
+                        kind: SignerKind::Account {
+                            key: signing_key,
+                            state: state.clone(),
+                        },
+                    }
                 }
-                AddressType::Contract => {
-                    let address =
-                        Address::try_from_val(env, &ScAddress::Contract(Hash(signer_bytes)))
-                            .unwrap();
-                    let test_signer = TestSigner { address, key: None };
+                AddressType::Ed25519Contract => TestSigner {
+                    address: contract_address(),
+                    kind: SignerKind::Ed25519Contract(SigningKey::from_bytes(&signer_bytes)),
+                },
+                AddressType::MultisigContract { threshold } => {
+                    let threshold = *threshold;
+                    let keys = (0..MULTISIG_SIGNERS)
+                        .map(|k| {
+                            let mut key_bytes = signer_bytes;
+                            key_bytes[0] = k as u8 + 1;
+                            SigningKey::from_bytes(&key_bytes)
+                        })
+                        .collect();
 
-                    test_signer
+                    TestSigner {
+                        address: contract_address(),
+                        kind: SignerKind::MultisigContract { keys, threshold },
+                    }
                 }
+                AddressType::RejectingContract => TestSigner {
+                    address: contract_address(),
+                    kind: SignerKind::RejectingContract,
+                },
             };
 
-            signers.push((test_signer, signer_bytes));
+            signers.push(test_signer);
         }
 
         signers
     }
 
-    pub fn setup_account_storage(&self, env: &Env) {
-        let signers_n_bytes = self.generate_signers_with_bytes(&env);
-        signers_n_bytes.iter().for_each(|(signer, bytes)| {
-            let sc_addr = ScAddress::try_from(signer.address.clone()).unwrap();
-            match sc_addr {
-                ScAddress::Account(account_id) => {
-                    let signing_key = SigningKey::from_bytes(bytes);
-                    create_default_account(&env, &account_id, vec![(&signing_key, 100)]);
-                    create_default_trustline(&env, &account_id);
+    /// Create the ledger entries of every Stellar account that exists,
+    /// and their trustlines to `asset`, if there is one.
+    pub fn setup_account_storage(&self, env: &Env, asset: Option<&ClassicAsset>) {
+        for signer in self.generate_signers(env) {
+            if let SignerKind::Account { key, state } = &signer.kind {
+                if !state.exists {
+                    continue;
+                }
+
+                let sc_addr = ScAddress::try_from(signer.address.clone()).unwrap();
+                let ScAddress::Account(account_id) = sc_addr else {
+                    unreachable!()
+                };
+                create_default_account(env, &account_id, vec![(key, 100)], state.balance, 0);
+                if let (Some(asset), Some(trustline)) = (asset, state.trustline()) {
+                    create_trustline(env, &account_id, asset, trustline);
                 }
-                ScAddress::Contract(_) => {}
             }
-        });
+        }
     }
 }
 
-fn create_default_account(env: &Env, account_id: &AccountId, signers: Vec<(&SigningKey, u32)>) {
+impl TestSigner {
+    /// Whether this signer is able to authorize calls at all.
+    pub fn can_authorize(&self) -> bool {
+        match &self.kind {
+            SignerKind::Account { state, .. } => state.exists,
+            SignerKind::RejectingContract => false,
+            _ => true,
+        }
+    }
+
+    /// The classic ledger state of this signer, if it is a Stellar account.
+    pub fn classic_account(&self) -> Option<&ClassicAccount> {
+        match &self.kind {
+            SignerKind::Account { state, .. } => Some(state),
+            _ => None,
+        }
+    }
+
+    /// Register the custom account contract for contract addresses.
+    ///
+    /// Contract addresses need to have registered contracts to be authorizers.
+    pub fn register_account_contract(&self, env: &Env) {
+        match &self.kind {
+            SignerKind::Account { .. } => { /* nop */ }
+            SignerKind::Ed25519Contract(key) => {
+                accounts::register_ed25519_account(env, &self.address, key);
+            }
+            SignerKind::MultisigContract { keys, threshold } => {
+                accounts::register_multisig_account(env, &self.address, keys, *threshold);
+            }
+            SignerKind::RejectingContract => {
+                accounts::register_rejecting_account(env, &self.address);
+            }
+        }
+    }
+
+    /// Sign an authorization payload,
+    /// producing the signature expected by this signer's `__check_auth`.
+    pub fn sign(&self, env: &Env, signature_payload: &[u8; 32]) -> ScVal {
+        self.sign_with(env, signature_payload, |key| key.clone())
+    }
+
+    /// Sign like [`TestSigner::sign`], but with keys that don't belong to any signer.
+    pub fn sign_with_wrong_keys(&self, env: &Env, signature_payload: &[u8; 32]) -> ScVal {
+        self.sign_with(env, signature_payload, |key| {
+            // Generated keys always have a zero in this byte.
+            let mut key_bytes = key.to_bytes();
+            key_bytes[1] = 0xff;
+            SigningKey::from_bytes(&key_bytes)
+        })
+    }
+
+    fn sign_with(
+        &self,
+        env: &Env,
+        signature_payload: &[u8; 32],
+        key_for: impl Fn(&SigningKey) -> SigningKey,
+    ) -> ScVal {
+        match &self.kind {
+            SignerKind::Account { key, .. } => {
+                let signature =
+                    accounts::sign_payload_for_account(env, &key_for(key), signature_payload);
+                soroban_sdk::vec![env, signature].try_into().unwrap()
+            }
+            SignerKind::Ed25519Contract(key) => {
+                accounts::sign_payload(env, &key_for(key), signature_payload)
+                    .try_into()
+                    .unwrap()
+            }
+            SignerKind::MultisigContract { keys, threshold } => {
+                let mut signatures: RustVec<_> = keys
+                    .iter()
+                    .map(|key| {
+                        accounts::sign_payload_for_account(env, &key_for(key), signature_payload)
+                    })
+                    .collect();
+                signatures.sort_by(|a, b| a.public_key.cmp(&b.public_key));
+                signatures.truncate(*threshold as usize);
+                let signatures = soroban_sdk::Vec::from_slice(env, &signatures);
+                signatures.try_into().unwrap()
+            }
+            SignerKind::RejectingContract => ScVal::Void,
+        }
+    }
+}
+
+fn create_default_account(
+    env: &Env,
+    account_id: &AccountId,
+    signers: Vec<(&SigningKey, u32)>,
+    balance: i64,
+    flags: u32,
+) {
     let key = LedgerKey::Account(LedgerKeyAccount {
         account_id: account_id.clone(),
     });
@@ -123,11 +357,11 @@ fn create_default_account(env: &Env, account_id: &AccountId, signers: Vec<(&Sign
     let ext = AccountEntryExt::V0;
     let acc_entry = AccountEntry {
         account_id: account_id.clone(),
-        balance: 10_000_000,
+        balance,
         seq_num: SequenceNumber(0),
         num_sub_entries: 0,
         inflation_dest: None,
-        flags: 0,
+        flags,
         home_domain: Default::default(),
         thresholds: Thresholds([1, 0, 0, 0]),
         signers: acc_signers.try_into().unwrap(),
@@ -150,27 +384,138 @@ fn create_default_account(env: &Env, account_id: &AccountId, signers: Vec<(&Sign
         .expect("ok");
 }
 
-fn create_default_trustline(env: &Env, account_id: &AccountId) {
-    // This is deterministically generated by Env::register_stellar_asset_contract,
-    // but could change if usage of the Env changes during the setup phase of the fuzzer.
-    let issuer_bytes: [u8; 32] = [
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-        0, 1,
-    ];
+impl ClassicAsset {
+    pub fn issuer(&self) -> AccountId {
+        AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(self.issuer)))
+    }
+
+    pub fn to_asset(&self) -> Asset {
+        match self.to_trustline_asset() {
+            TrustLineAsset::CreditAlphanum4(asset) => Asset::CreditAlphanum4(asset),
+            TrustLineAsset::CreditAlphanum12(asset) => Asset::CreditAlphanum12(asset),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn to_trustline_asset(&self) -> TrustLineAsset {
+        if self.code.len() <= 4 {
+            let mut asset_code = [0; 4];
+            asset_code[..self.code.len()].copy_from_slice(&self.code);
+            TrustLineAsset::CreditAlphanum4(AlphaNum4 {
+                asset_code: AssetCode4(asset_code),
+                issuer: self.issuer(),
+            })
+        } else {
+            let mut asset_code = [0; 12];
+            asset_code[..self.code.len()].copy_from_slice(&self.code);
+            TrustLineAsset::CreditAlphanum12(AlphaNum12 {
+                asset_code: AssetCode12(asset_code),
+                issuer: self.issuer(),
+            })
+        }
+    }
+}
+
+/// Register the Stellar Asset Contract for `asset`, with `admin` as its admin.
+///
+/// This is like `Env::register_stellar_asset_contract`, but for any asset.
+/// The issuer is created with the `AUTH_REVOCABLE` and `AUTH_CLAWBACK_ENABLED` flags;
+/// without them the admin can't deauthorize holders,
+/// nor claw back from contract holders.
+pub fn register_stellar_asset_contract(
+    env: &Env,
+    admin: &Address,
+    asset: &ClassicAsset,
+) -> Address {
+    create_default_account(
+        env,
+        &asset.issuer(),
+        vec![],
+        10_000_000,
+        AccountFlags::RevocableFlag as u32 | AccountFlags::ClawbackEnabledFlag as u32,
+    );
+
+    let create = HostFunction::CreateContract(CreateContractArgs {
+        contract_id_preimage: ContractIdPreimage::Asset(asset.to_asset()),
+        executable: ContractExecutable::StellarAsset,
+    });
+    let token_contract_id = env.host().invoke_function(create).expect("ok");
+    let token_contract_id = Address::try_from_val(env, &token_contract_id).unwrap();
+
+    // The issuer starts as the admin, and we don't have its keys,
+    // so hand over to the real admin with auth recording enabled.
+    let prev_auth_manager = env.host().snapshot_auth_manager().unwrap();
+    env.host().switch_to_recording_auth(true).unwrap();
+    StellarAssetClient::new(env, &token_contract_id).set_admin(admin);
+    env.host().set_auth_manager(prev_auth_manager).unwrap();
+
+    token_contract_id
+}
+
+/// Register the Stellar Asset Contract for native lumens.
+///
+/// It has no admin, so it can't mint,
+/// and its supply is the balances of existing accounts.
+pub fn register_native_asset_contract(env: &Env) -> Address {
+    let create = HostFunction::CreateContract(CreateContractArgs {
+        contract_id_preimage: ContractIdPreimage::Asset(Asset::Native),
+        executable: ContractExecutable::StellarAsset,
+    });
+    let token_contract_id = env.host().invoke_function(create).expect("ok");
+    let token_contract_id = Address::try_from_val(env, &token_contract_id).unwrap();
 
-    let issuer = AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(issuer_bytes)));
-    let asset = TrustLineAsset::CreditAlphanum4(AlphaNum4 {
-        asset_code: AssetCode4([b'a', b'a', b'a', 0]),
-        issuer: issuer,
+    // Other assets extend their instance when `set_admin` is called,
+    // but this one may go unused long enough to be archived.
+    env.as_contract(&token_contract_id, || {
+        env.storage()
+            .instance()
+            .extend_ttl(crate::DAY_IN_LEDGERS, crate::DAY_IN_LEDGERS * 7);
     });
 
+    token_contract_id
+}
+
+/// Read back the asset of the Stellar Asset Contract at `token_contract_id`.
+///
+/// The contract's name is the asset code and the issuer's strkey,
+/// separated by a colon.
+pub fn stellar_asset_of(env: &Env, token_contract_id: &Address) -> ClassicAsset {
+    let name = token::Client::new(env, token_contract_id).name();
+    let mut name_bytes = vec![0; name.len() as usize];
+    name.copy_into_slice(&mut name_bytes);
+    let name = std::str::from_utf8(&name_bytes).unwrap();
+
+    let (code, issuer) = name.split_once(':').unwrap();
+    let issuer = stellar_strkey::ed25519::PublicKey::from_string(issuer).unwrap();
+
+    ClassicAsset {
+        code: code.as_bytes().to_vec(),
+        issuer: issuer.0,
+    }
+}
+
+fn create_trustline(
+    env: &Env,
+    account_id: &AccountId,
+    asset: &ClassicAsset,
+    trustline: &Trustline,
+) {
+    let asset = asset.to_trustline_asset();
+
     let key = LedgerKey::Trustline(LedgerKeyTrustLine {
         account_id: account_id.clone(),
         asset: asset.clone(),
     });
 
-    let flags =
-        TrustLineFlags::AuthorizedFlag as u32 | TrustLineFlags::TrustlineClawbackEnabledFlag as u32;
+    let mut flags = 0;
+    if trustline.authorized {
+        flags |= TrustLineFlags::AuthorizedFlag as u32;
+    } else if trustline.authorized_to_maintain_liabilities {
+        flags |= TrustLineFlags::AuthorizedToMaintainLiabilitiesFlag as u32;
+    }
+    if trustline.clawback_enabled {
+        flags |= TrustLineFlags::TrustlineClawbackEnabledFlag as u32;
+    }
 
     let ext = TrustLineEntryExt::V0;
 
@@ -178,7 +523,7 @@ fn create_default_trustline(env: &Env, account_id: &AccountId) {
         account_id: account_id.clone(),
         asset,
         balance: 0,
-        limit: i64::MAX,
+        limit: trustline.limit,
         flags,
         ext,
     };
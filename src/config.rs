@@ -1,9 +1,19 @@
+use crate::addrgen::{self, ClassicAsset};
+use crate::fuzz::{AddressResult, BoolResult, UnitResult};
+use crate::input::{InitParams, MAX_NUMBER_OF_ADDRESSES, MIN_NUMBER_OF_ADDRESSES};
+use crate::spec::{DeclaredTokenOps, TokenDeclaration};
+use soroban_env_host::Env as _;
 use soroban_sdk::testutils::Address as _;
+use soroban_sdk::testutils::Ledger as _;
 use soroban_sdk::token;
 use soroban_sdk::token::StellarAssetClient;
-use soroban_sdk::xdr::SorobanAuthorizationEntry;
-use soroban_sdk::{Address, Env};
-use soroban_sdk::{Error, InvokeError, TryFromVal, Val};
+use soroban_sdk::xdr::{ScErrorCode, ScErrorType, SorobanAuthorizationEntry};
+use soroban_sdk::{Address, Bytes, Env};
+use soroban_sdk::{Error, IntoVal, Symbol, Val};
+use std::any::TypeId;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
 /// Token-specific configuration and customization.
 ///
@@ -13,10 +23,14 @@ use soroban_sdk::{Error, InvokeError, TryFromVal, Val};
 /// customized to their token.
 pub struct Config {
     kind: TokenKind,
+    accessors_may_extend_ttl: bool,
+    number_of_addresses: Option<usize>,
+    upgrade: Option<Box<UpgradeFn>>,
 }
 
 pub enum TokenKind {
     Native,
+    NativeLumens,
     Contract(ContractTokenConfig),
 }
 
@@ -29,7 +43,14 @@ pub trait ContractTokenOps {
     /// contract-specific one-time initialization.
     ///
     /// This function will be called once.
-    fn register_contract_init(&self, env: &Env, admin: &Address) -> Address;
+    /// Returns `None` if the token rejects `params`,
+    /// in which case the input is skipped.
+    fn register_contract_init(
+        &self,
+        env: &Env,
+        admin: &Address,
+        params: &InitParams,
+    ) -> Option<Address>;
 
     /// Register the contract with the environment.
     ///
@@ -45,64 +66,385 @@ pub trait ContractTokenOps {
         token_contract_id: &Address,
     ) -> Box<dyn TokenAdminClient<'a> + 'a>;
 
+    /// Call the token's initialization function again,
+    /// which must fail, since the token is already initialized.
+    ///
+    /// Every auth is mocked during the call.
+    /// Returns `None` by default, for tokens that have no such function.
+    fn try_reinitialize(
+        &self,
+        _env: &Env,
+        _token_contract_id: &Address,
+        _admin: &Address,
+        _params: &InitParams,
+    ) -> Option<UnitResult> {
+        None
+    }
+
     fn keep_contracts_alive(&self, env: &Env, token_contract_id: &Address) {
         let token_client = token::Client::new(&env, &token_contract_id);
         let r = token_client.try_allowance(&Address::generate(&env), &Address::generate(&env));
         assert!(r.is_ok());
     }
+
+    /// Whether [`TokenAdminClient::try_mint`] emits a standard
+    /// `("mint", admin, to)` event from the token contract.
+    ///
+    /// Tokens that mint through some other mechanism,
+    /// e.g. by joining a liquidity pool, should return `false`.
+    fn mint_emits_standard_event(&self) -> bool {
+        true
+    }
+
+    /// Whether [`TokenAdminClient::try_mint`] requires the admin's auth.
+    ///
+    /// If it does, the fuzzer asserts that minting fails
+    /// without the admin's auth.
+    /// Tokens that let anybody mint, e.g. by joining a liquidity pool,
+    /// should return `false`.
+    fn mint_requires_admin_auth(&self) -> bool {
+        true
+    }
 }
 
 pub trait TokenAdminClient<'a> {
     /// Mint tokens.
-    fn try_mint(
+    fn try_mint(&self, to: &Address, amount: &i128) -> UnitResult;
+
+    /// The name and arguments of the function `try_mint` calls,
+    /// which the admin's auth is signed for.
+    ///
+    /// Defaults to `mint(to, amount)`, as in the `StellarAssetInterface`.
+    fn mint_invocation(
         &self,
+        env: &Env,
         to: &Address,
         amount: &i128,
-    ) -> Result<Result<(), <() as TryFromVal<Env, Val>>::Error>, Result<Error, InvokeError>>;
+    ) -> (&str, soroban_sdk::Vec<Val>) {
+        ("mint", (to, *amount).into_val(env))
+    }
 
-    /// Unused.
+    /// Set a new admin.
     ///
-    /// This is just defined to make sure the lifetimes work;
-    /// we don't actually need to implement it yet.
-    fn set_auths(&self, _auths: &'a [SorobanAuthorizationEntry]) -> Box<dyn TokenAdminClient> {
-        todo!()
+    /// Tokens that don't support changing the admin
+    /// don't need to implement this.
+    /// It returns `None` if unimplemented.
+    fn try_set_admin(&self, _new_admin: &Address) -> Option<UnitResult> {
+        None
     }
+
+    /// Claw back tokens from a holder.
+    ///
+    /// Like [`TokenAdminClient::try_set_admin`] this is optional,
+    /// as are the other admin functions of the Stellar Asset Contract below.
+    fn try_clawback(&self, _from: &Address, _amount: &i128) -> Option<UnitResult> {
+        None
+    }
+
+    /// Authorize or deauthorize a holder.
+    fn try_set_authorized(&self, _id: &Address, _authorize: &bool) -> Option<UnitResult> {
+        None
+    }
+
+    /// Whether a holder is authorized.
+    fn try_authorized(&self, _id: &Address) -> Option<BoolResult> {
+        None
+    }
+
+    /// The current admin.
+    fn try_admin(&self) -> Option<AddressResult> {
+        None
+    }
+
+    /// Create a client that makes calls with the given auths,
+    /// like the `set_auths` method of generated contract clients.
+    ///
+    /// The fuzzer signs the auths for the admin's `mint` call
+    /// the same way it signs the auths of every other call.
+    fn set_auths<'b>(
+        &self,
+        auths: &'b [SorobanAuthorizationEntry],
+    ) -> Box<dyn TokenAdminClient<'b> + 'b>;
 }
 
-struct NativeTokenAdminClient<'a> {
-    admin_client: StellarAssetClient<'a>,
+/// An admin client for tokens that implement the admin functions
+/// of the `StellarAssetInterface`, including the Stellar Asset Contract itself.
+pub(crate) struct StellarAssetAdminClient<'a> {
+    pub(crate) admin_client: StellarAssetClient<'a>,
+    pub(crate) supported: SupportedAdminFunctions,
+}
+
+/// Which of the optional admin functions
+/// of the `StellarAssetInterface` a token implements.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct SupportedAdminFunctions {
+    pub(crate) set_admin: bool,
+    pub(crate) clawback: bool,
+    pub(crate) set_authorized: bool,
+    pub(crate) authorized: bool,
+    pub(crate) admin: bool,
+}
+
+type RegisterFn = dyn Fn(&Env, Option<&Address>) -> Address;
+type InitializeFn = dyn Fn(&Env, &Address, &Address, &InitParams) -> UnitResult;
+type UpgradeFn = dyn Fn(&Env, &Address);
+
+/// The admin functions detected for each token built with
+/// [`Config::stellar_asset_interface`], by the types of its closures,
+/// and, for WASM tokens, by the path of the WASM.
+///
+/// Drivers construct their `Config` on every fuzz iteration,
+/// but detection registers and calls a scratch token,
+/// so it is only done the first time.
+static DETECTED: Mutex<BTreeMap<(TypeId, Option<PathBuf>), SupportedAdminFunctions>> =
+    Mutex::new(BTreeMap::new());
+
+/// WASM read by [`Config::stellar_asset_interface_wasm`], by path.
+static WASM_FILES: Mutex<BTreeMap<PathBuf, Arc<[u8]>>> = Mutex::new(BTreeMap::new());
+
+/// [`ContractTokenOps`] for [`Config::stellar_asset_interface`].
+struct StellarAssetInterfaceOps {
+    register: Box<RegisterFn>,
+    initialize: Box<InitializeFn>,
+    supported: SupportedAdminFunctions,
 }
 
 impl Config {
     pub fn native() -> Config {
         Config {
             kind: TokenKind::Native,
+            accessors_may_extend_ttl: true,
+            number_of_addresses: None,
+            upgrade: None,
+        }
+    }
+
+    /// Fuzz the Stellar Asset Contract for native lumens.
+    ///
+    /// Unlike [`Config::native`] there is no admin and no trustlines:
+    /// the balances of Stellar accounts are the balances of their account entries,
+    /// which must stay above the minimum balance required by the base reserve.
+    pub fn native_lumens() -> Config {
+        Config {
+            kind: TokenKind::NativeLumens,
+            accessors_may_extend_ttl: true,
+            number_of_addresses: None,
+            upgrade: None,
         }
     }
 
     pub fn contract(ops: impl ContractTokenOps + 'static) -> Config {
         Config {
             kind: TokenKind::Contract(ContractTokenConfig { ops: Box::new(ops) }),
+            accessors_may_extend_ttl: true,
+            number_of_addresses: None,
+            upgrade: None,
         }
     }
 
-    pub fn register_contract_init(&self, env: &Env, admin: &Address) -> Address {
+    /// Fuzz a token that implements the admin functions of
+    /// [`StellarAssetInterface`](soroban_sdk::token::StellarAssetInterface),
+    /// without writing a [`ContractTokenOps`].
+    ///
+    /// `register` registers the contract, at the given address if any,
+    /// e.g. with `Env::register_contract`.
+    /// `initialize` calls the token's initialization function,
+    /// with the contract address, the admin, and the fuzzed parameters,
+    /// and returns the result of the call.
+    /// It is called once to initialize the token,
+    /// and then again by the fuzzer, expecting it to fail.
+    ///
+    /// Admin commands are made through [`StellarAssetClient`].
+    /// `mint` is required, but the token may leave out any of
+    /// `set_admin`, `clawback`, `set_authorized`, `authorized` and `admin`;
+    /// which ones exist is detected here by calling them
+    /// on a scratch instance of the token,
+    /// once per process for each pair of closure types.
+    pub fn stellar_asset_interface<R, I>(register: R, initialize: I) -> Config
+    where
+        R: Fn(&Env, Option<&Address>) -> Address + 'static,
+        I: Fn(&Env, &Address, &Address, &InitParams) -> UnitResult + 'static,
+    {
+        Config::stellar_asset_interface_detected(
+            (TypeId::of::<(R, I)>(), None),
+            register,
+            initialize,
+        )
+    }
+
+    fn stellar_asset_interface_detected(
+        key: (TypeId, Option<PathBuf>),
+        register: impl Fn(&Env, Option<&Address>) -> Address + 'static,
+        initialize: impl Fn(&Env, &Address, &Address, &InitParams) -> UnitResult + 'static,
+    ) -> Config {
+        let supported = *DETECTED
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_insert_with(|| SupportedAdminFunctions::detect(&register, &initialize));
+        Config::contract(StellarAssetInterfaceOps {
+            register: Box::new(register),
+            initialize: Box::new(initialize),
+            supported,
+        })
+    }
+
+    /// Fuzz a token compiled to WASM, read from `path`,
+    /// that implements the admin functions of
+    /// [`StellarAssetInterface`](soroban_sdk::token::StellarAssetInterface).
+    ///
+    /// This is like [`Config::stellar_asset_interface`],
+    /// but runs the same code that is deployed,
+    /// with `panic = "abort"` and whatever else its build does.
+    /// The token is registered with `Env::register_contract_wasm`.
+    /// Every `reregister_contract` uploads the WASM again,
+    /// but with [`upgrade_contract_wasm`],
+    /// since `register_contract_wasm` would replace the token's instance storage.
+    ///
+    /// The WASM is read, and its admin functions detected,
+    /// only the first time for each path.
+    pub fn stellar_asset_interface_wasm<I>(path: impl AsRef<Path>, initialize: I) -> Config
+    where
+        I: Fn(&Env, &Address, &Address, &InitParams) -> UnitResult + 'static,
+    {
+        let path = path.as_ref();
+        let wasm = WASM_FILES
+            .lock()
+            .unwrap()
+            .entry(path.to_owned())
+            .or_insert_with(|| {
+                std::fs::read(path)
+                    .unwrap_or_else(|e| panic!("failed to read token WASM {}: {e}", path.display()))
+                    .into()
+            })
+            .clone();
+        Config::stellar_asset_interface_detected(
+            (TypeId::of::<I>(), Some(path.to_owned())),
+            move |env, token_contract_id| register_wasm(env, token_contract_id, &wasm),
+            initialize,
+        )
+    }
+
+    /// Fuzz a token compiled to WASM, without writing any Rust,
+    /// given a [`TokenDeclaration`] of how to initialize it and mint it.
+    ///
+    /// Which of `set_admin`, `clawback`, `set_authorized`, `authorized` and `admin`
+    /// the token has is read from its contract spec,
+    /// and those are called through [`StellarAssetClient`].
+    /// The WASM is registered like [`Config::stellar_asset_interface_wasm`] does.
+    pub fn declared(declaration: TokenDeclaration) -> Config {
+        Config::contract(DeclaredTokenOps(declaration))
+    }
+
+    /// Set whether accessors like `balance` and `allowance`
+    /// may extend the TTL of the token's storage.
+    ///
+    /// Accessors may never change the contents of the token's storage.
+    /// TTL extensions are allowed by default.
+    pub fn with_accessor_ttl_extensions(mut self, allow: bool) -> Config {
+        self.accessors_may_extend_ttl = allow;
+        self
+    }
+
+    pub fn accessors_may_extend_ttl(&self) -> bool {
+        self.accessors_may_extend_ttl
+    }
+
+    /// Use exactly this many addresses in every run.
+    ///
+    /// By default the number of addresses is fuzzed,
+    /// between [`MIN_NUMBER_OF_ADDRESSES`] and [`MAX_NUMBER_OF_ADDRESSES`].
+    pub fn with_number_of_addresses(mut self, number_of_addresses: usize) -> Config {
+        assert!(
+            (MIN_NUMBER_OF_ADDRESSES..=MAX_NUMBER_OF_ADDRESSES).contains(&number_of_addresses),
+            "number of addresses must be between {MIN_NUMBER_OF_ADDRESSES} and {MAX_NUMBER_OF_ADDRESSES}",
+        );
+        self.number_of_addresses = Some(number_of_addresses);
+        self
+    }
+
+    pub fn number_of_addresses(&self) -> Option<usize> {
+        self.number_of_addresses
+    }
+
+    /// Upgrade the token to a "v2" implementation with `upgrade`,
+    /// once the fuzzer issues [`Command::Upgrade`](crate::input::Command::Upgrade).
+    ///
+    /// `upgrade` is given the token's address,
+    /// and must replace its implementation, keeping its storage,
+    /// e.g. by registering a native contract again with `Env::register_contract`,
+    /// or with [`upgrade_contract_wasm`] for WASM contracts.
+    /// It is called in place of reregistering the original implementation,
+    /// every time the `Env` is recreated after the upgrade.
+    ///
+    /// Without an upgrade, `Upgrade` commands do nothing.
+    pub fn with_upgrade(mut self, upgrade: impl Fn(&Env, &Address) + 'static) -> Config {
+        self.upgrade = Some(Box::new(upgrade));
+        self
+    }
+
+    /// Register and initialize the token,
+    /// or return `None` if it rejects `params`.
+    ///
+    /// `asset` is the classic asset wrapped by the native token,
+    /// and is ignored by contract tokens.
+    /// The native tokens ignore `params`.
+    pub fn register_contract_init(
+        &self,
+        env: &Env,
+        admin: &Address,
+        params: &InitParams,
+        asset: &ClassicAsset,
+    ) -> Option<Address> {
         match &self.kind {
-            TokenKind::Native => env.register_stellar_asset_contract(admin.clone()),
-            TokenKind::Contract(cfg) => cfg.register_contract_init(env, admin),
+            TokenKind::Native => Some(addrgen::register_stellar_asset_contract(env, admin, asset)),
+            TokenKind::NativeLumens => {
+                env.ledger()
+                    .with_mut(|ledger| ledger.base_reserve = addrgen::BASE_RESERVE);
+                Some(addrgen::register_native_asset_contract(env))
+            }
+            TokenKind::Contract(cfg) => cfg.register_contract_init(env, admin, params),
         }
     }
 
-    pub fn reregister_contract(&self, env: &Env, token_contract_id: &Address) {
+    pub fn try_reinitialize(
+        &self,
+        env: &Env,
+        token_contract_id: &Address,
+        admin: &Address,
+        params: &InitParams,
+    ) -> Option<UnitResult> {
         match &self.kind {
-            TokenKind::Native => { /* nop */ }
+            TokenKind::Native | TokenKind::NativeLumens => None,
+            TokenKind::Contract(cfg) => cfg.try_reinitialize(env, token_contract_id, admin, params),
+        }
+    }
+
+    /// The classic asset wrapped by the token, as registered,
+    /// or `None` if it is not a Stellar Asset Contract.
+    pub fn stellar_asset(&self, env: &Env, token_contract_id: &Address) -> Option<ClassicAsset> {
+        match &self.kind {
+            TokenKind::Native => Some(addrgen::stellar_asset_of(env, token_contract_id)),
+            TokenKind::NativeLumens | TokenKind::Contract(_) => None,
+        }
+    }
+
+    /// Register the token again in a recreated `Env`,
+    /// as its upgraded implementation if `upgraded` and it has one.
+    pub fn reregister_contract(&self, env: &Env, token_contract_id: &Address, upgraded: bool) {
+        if let (true, Some(upgrade)) = (upgraded, &self.upgrade) {
+            upgrade(env, token_contract_id);
+            return;
+        }
+        match &self.kind {
+            TokenKind::Native | TokenKind::NativeLumens => { /* nop */ }
             TokenKind::Contract(cfg) => cfg.reregister_contract(env, token_contract_id),
         }
     }
 
     pub fn keep_contracts_alive(&self, env: &Env, token_contract_id: &Address) {
         match &self.kind {
-            TokenKind::Native => { /* nop */ }
+            TokenKind::Native | TokenKind::NativeLumens => { /* nop */ }
             TokenKind::Contract(cfg) => cfg.keep_contracts_alive(env, token_contract_id),
         }
     }
@@ -113,31 +455,233 @@ impl Config {
         token_contract_id: &Address,
     ) -> Box<dyn TokenAdminClient<'a> + 'a> {
         match &self.kind {
-            TokenKind::Native => Box::new(NativeTokenAdminClient {
+            TokenKind::Native => Box::new(StellarAssetAdminClient {
                 admin_client: { StellarAssetClient::new(env, &token_contract_id) },
+                supported: SupportedAdminFunctions::ALL,
+            }),
+            TokenKind::NativeLumens => Box::new(StellarAssetAdminClient {
+                admin_client: { StellarAssetClient::new(env, token_contract_id) },
+                supported: SupportedAdminFunctions::NONE_BUT_AUTHORIZED,
             }),
             TokenKind::Contract(cfg) => cfg.new_admin_client(env, token_contract_id),
         }
     }
+
+    pub fn mint_emits_standard_event(&self) -> bool {
+        match &self.kind {
+            TokenKind::Native | TokenKind::NativeLumens => true,
+            TokenKind::Contract(cfg) => cfg.mint_emits_standard_event(),
+        }
+    }
+
+    pub fn mint_requires_admin_auth(&self) -> bool {
+        match &self.kind {
+            TokenKind::Native | TokenKind::NativeLumens => true,
+            TokenKind::Contract(cfg) => cfg.mint_requires_admin_auth(),
+        }
+    }
+
+    /// The Stellar Asset Contract for native lumens can't be burned.
+    pub fn burnable(&self) -> bool {
+        match &self.kind {
+            TokenKind::NativeLumens => false,
+            TokenKind::Native | TokenKind::Contract(_) => true,
+        }
+    }
+
+    /// The Stellar Asset Contract keeps the balances of
+    /// Stellar accounts in their trustlines to its asset,
+    /// and follows trustline semantics for them.
+    pub fn balances_in_trustlines(&self) -> bool {
+        match &self.kind {
+            TokenKind::Native => true,
+            TokenKind::NativeLumens | TokenKind::Contract(_) => false,
+        }
+    }
+
+    /// The native lumens token keeps the balances of Stellar accounts
+    /// in their account entries, and won't let them fall below this.
+    pub fn account_min_balance(&self) -> Option<i64> {
+        match &self.kind {
+            TokenKind::NativeLumens => Some(addrgen::MIN_ACCOUNT_BALANCE),
+            TokenKind::Native | TokenKind::Contract(_) => None,
+        }
+    }
+
+    /// The Stellar Asset Contract appends the SEP-11 asset name,
+    /// which is also its `name`, to the topics of every event.
+    pub fn events_include_name_topic(&self) -> bool {
+        match &self.kind {
+            TokenKind::Native | TokenKind::NativeLumens => true,
+            TokenKind::Contract(_) => false,
+        }
+    }
 }
 
-impl<'a> TokenAdminClient<'a> for NativeTokenAdminClient<'a> {
-    fn try_mint(
-        &self,
-        to: &Address,
-        amount: &i128,
-    ) -> Result<Result<(), <() as TryFromVal<Env, Val>>::Error>, Result<Error, InvokeError>> {
+impl<'a> TokenAdminClient<'a> for StellarAssetAdminClient<'a> {
+    fn try_mint(&self, to: &Address, amount: &i128) -> UnitResult {
         self.admin_client.try_mint(to, amount)
     }
 
-    fn set_auths(&self, _auths: &'a [SorobanAuthorizationEntry]) -> Box<dyn TokenAdminClient> {
-        todo!()
+    fn try_set_admin(&self, new_admin: &Address) -> Option<UnitResult> {
+        self.supported
+            .set_admin
+            .then(|| self.admin_client.try_set_admin(new_admin))
+    }
+
+    fn try_clawback(&self, from: &Address, amount: &i128) -> Option<UnitResult> {
+        self.supported
+            .clawback
+            .then(|| self.admin_client.try_clawback(from, amount))
+    }
+
+    fn try_set_authorized(&self, id: &Address, authorize: &bool) -> Option<UnitResult> {
+        self.supported
+            .set_authorized
+            .then(|| self.admin_client.try_set_authorized(id, authorize))
+    }
+
+    fn try_authorized(&self, id: &Address) -> Option<BoolResult> {
+        self.supported
+            .authorized
+            .then(|| self.admin_client.try_authorized(id))
+    }
+
+    fn try_admin(&self) -> Option<AddressResult> {
+        self.supported.admin.then(|| self.admin_client.try_admin())
+    }
+
+    fn set_auths<'b>(
+        &self,
+        auths: &'b [SorobanAuthorizationEntry],
+    ) -> Box<dyn TokenAdminClient<'b> + 'b> {
+        let admin_client = &self.admin_client;
+        Box::new(StellarAssetAdminClient {
+            admin_client: StellarAssetClient::new(&admin_client.env, &admin_client.address)
+                .set_auths(auths),
+            supported: self.supported,
+        })
+    }
+}
+
+impl SupportedAdminFunctions {
+    const ALL: SupportedAdminFunctions = SupportedAdminFunctions {
+        set_admin: true,
+        clawback: true,
+        set_authorized: true,
+        authorized: true,
+        admin: true,
+    };
+
+    /// The native lumens token has no admin,
+    /// but `authorized` works, and is always true.
+    const NONE_BUT_AUTHORIZED: SupportedAdminFunctions = SupportedAdminFunctions {
+        set_admin: false,
+        clawback: false,
+        set_authorized: false,
+        authorized: true,
+        admin: false,
+    };
+
+    /// Call each optional admin function on a scratch instance of the token,
+    /// in its own `Env`, and see which ones the host can't find.
+    fn detect(register: &RegisterFn, initialize: &InitializeFn) -> SupportedAdminFunctions {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let holder = Address::generate(&env);
+        let token_contract_id = register(&env, None);
+        let r = initialize(&env, &token_contract_id, &admin, &InitParams::default());
+        assert!(
+            matches!(r, Ok(Ok(()))),
+            "the token rejected the default initialization parameters"
+        );
+
+        // The `try_` client methods narrow most host errors to the same error,
+        // so call the host directly to see why a call failed.
+        // Native contracts and WASM contracts report unknown functions differently.
+        let unknown_function = |e: Error| {
+            e.is_code(ScErrorCode::MissingValue)
+                && (e.is_type(ScErrorType::Context) || e.is_type(ScErrorType::WasmVm))
+        };
+        let exists = |fn_name: &str, args: soroban_sdk::Vec<Val>| {
+            let r = env.host().call(
+                token_contract_id.to_object(),
+                Symbol::new(&env, fn_name).to_symbol_val(),
+                args.to_object(),
+            );
+            !matches!(r, Err(e) if unknown_function(e.error))
+        };
+
+        SupportedAdminFunctions {
+            admin: exists("admin", soroban_sdk::Vec::new(&env)),
+            authorized: exists("authorized", (&holder,).into_val(&env)),
+            set_authorized: exists("set_authorized", (&holder, true).into_val(&env)),
+            clawback: exists("clawback", (&holder, 0_i128).into_val(&env)),
+            // Last, since it may succeed, though it doesn't change the admin.
+            set_admin: exists("set_admin", (&admin,).into_val(&env)),
+        }
+    }
+}
+
+impl ContractTokenOps for StellarAssetInterfaceOps {
+    fn register_contract_init(
+        &self,
+        env: &Env,
+        admin: &Address,
+        params: &InitParams,
+    ) -> Option<Address> {
+        let token_contract_id = (self.register)(env, None);
+        let r = (self.initialize)(env, &token_contract_id, admin, params);
+        matches!(r, Ok(Ok(()))).then_some(token_contract_id)
+    }
+
+    fn try_reinitialize(
+        &self,
+        env: &Env,
+        token_contract_id: &Address,
+        admin: &Address,
+        params: &InitParams,
+    ) -> Option<UnitResult> {
+        Some((self.initialize)(env, token_contract_id, admin, params))
+    }
+
+    fn reregister_contract(&self, env: &Env, token_contract_id: &Address) {
+        (self.register)(env, Some(token_contract_id));
+    }
+
+    fn new_admin_client<'a>(
+        &self,
+        env: &Env,
+        token_contract_id: &Address,
+    ) -> Box<dyn TokenAdminClient<'a> + 'a> {
+        Box::new(StellarAssetAdminClient {
+            admin_client: StellarAssetClient::new(env, token_contract_id),
+            supported: self.supported,
+        })
     }
 }
 
 impl ContractTokenConfig {
-    pub fn register_contract_init(&self, env: &Env, admin: &Address) -> Address {
-        self.ops.register_contract_init(env, admin)
+    pub fn register_contract_init(
+        &self,
+        env: &Env,
+        admin: &Address,
+        params: &InitParams,
+    ) -> Option<Address> {
+        self.ops.register_contract_init(env, admin, params)
+    }
+
+    pub fn try_reinitialize(
+        &self,
+        env: &Env,
+        token_contract_id: &Address,
+        admin: &Address,
+        params: &InitParams,
+    ) -> Option<UnitResult> {
+        self.ops
+            .try_reinitialize(env, token_contract_id, admin, params)
     }
 
     pub fn reregister_contract(&self, env: &Env, token_contract_id: &Address) {
@@ -155,4 +699,42 @@ impl ContractTokenConfig {
     pub fn keep_contracts_alive(&self, env: &Env, token_contract_id: &Address) {
         self.ops.keep_contracts_alive(env, token_contract_id)
     }
+
+    pub fn mint_emits_standard_event(&self) -> bool {
+        self.ops.mint_emits_standard_event()
+    }
+
+    pub fn mint_requires_admin_auth(&self) -> bool {
+        self.ops.mint_requires_admin_auth()
+    }
+}
+
+/// Upgrade the contract at `contract_id` to `wasm`, keeping its storage,
+/// as the contract itself would with `update_current_contract_wasm`.
+///
+/// Registering the WASM again with `Env::register_contract_wasm`
+/// would instead replace the contract's instance storage.
+pub fn upgrade_contract_wasm(env: &Env, contract_id: &Address, wasm: &[u8]) {
+    let wasm_hash = env
+        .deployer()
+        .upload_contract_wasm(Bytes::from_slice(env, wasm));
+    env.as_contract(contract_id, || {
+        env.deployer().update_current_contract_wasm(wasm_hash);
+    });
+}
+
+/// Register a WASM contract, or, given the address of one already registered,
+/// upload its WASM again, keeping its storage.
+pub(crate) fn register_wasm(
+    env: &Env,
+    token_contract_id: Option<&Address>,
+    wasm: &[u8],
+) -> Address {
+    match token_contract_id {
+        None => env.register_contract_wasm(None, wasm),
+        Some(token_contract_id) => {
+            upgrade_contract_wasm(env, token_contract_id, wasm);
+            token_contract_id.clone()
+        }
+    }
 }
@@ -1,41 +1,56 @@
-use crate::addrgen::{AddressGenerator, TestSigner};
+use crate::addrgen::{AddressGenerator, TestSigner, Trustline};
 use crate::config::*;
 use crate::input::*;
+use crate::ledgerdiff::{diff_ledger, ChangeKind, StorageType};
 use crate::util::*;
 use crate::DAY_IN_LEDGERS;
-use ed25519_dalek::{Signer, SigningKey};
 use itertools::Itertools;
 use libfuzzer_sys::Corpus;
 use num_bigint::BigInt;
 use sha2::{Digest, Sha256};
+use soroban_env_host::Env as _;
 use soroban_sdk::testutils::Snapshot;
-use soroban_sdk::testutils::{Events, Ledger, LedgerInfo};
-use soroban_sdk::xdr::{ContractDataDurability, LedgerKey};
+use soroban_sdk::testutils::{
+    AuthorizedFunction, AuthorizedInvocation, Events, Ledger, LedgerInfo,
+};
 use soroban_sdk::xdr::{
-    HashIdPreimage, HashIdPreimageSorobanAuthorization, InvokeContractArgs, ScAddress, ScSymbol,
-    ScVal, SorobanAddressCredentials, SorobanAuthorizationEntry, SorobanAuthorizedFunction,
+    ContractEvent, ContractEventBody, ContractEventType, ContractEventV0, Hash, HashIdPreimage,
+    HashIdPreimageSorobanAuthorization, Int128Parts, InvokeContractArgs, LedgerKey,
+    LedgerKeyContractData, ScAddress, ScMapEntry, ScNonceKey, ScSymbol, ScVal,
+    SorobanAddressCredentials, SorobanAuthorizationEntry, SorobanAuthorizedFunction,
     SorobanAuthorizedInvocation, SorobanCredentials, VecM,
 };
 use soroban_sdk::xdr::{Limited, Limits, WriteXdr};
 use soroban_sdk::xdr::{ScErrorCode, ScErrorType};
 use soroban_sdk::{
-    contract, contractimpl, contracttype, token::Client, Address, Bytes, BytesN, Env, Error,
-    IntoVal, InvokeError, TryFromVal, Val,
+    symbol_short, token::Client, Address, Bytes, ConversionError, Env, Error, IntoVal, InvokeError,
+    String, Symbol, TryFromVal, Val,
 };
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::vec::Vec as RustVec;
 
 // Don't know where this number comes from.
 const MAX_LEDGERS_TO_ADVANCE: u32 = 4095;
 
-type TokenContractResult =
-    Result<Result<(), <() as TryFromVal<Env, Val>>::Error>, Result<Error, InvokeError>>;
+/// The result of a `try_` client call returning nothing.
+pub type UnitResult = Result<Result<(), ConversionError>, Result<Error, InvokeError>>;
+
+/// The result of a `try_` client call returning a `bool`.
+pub type BoolResult = Result<Result<bool, ConversionError>, Result<Error, InvokeError>>;
 
-pub fn fuzz_token(config: Config, input: Input) -> Corpus {
+/// The result of a `try_` client call returning an `Address`.
+pub type AddressResult = Result<Result<Address, ConversionError>, Result<Error, InvokeError>>;
+
+pub fn fuzz_token(config: Config, mut input: Input) -> Corpus {
     if input.transactions.iter().all(|tx| tx.commands.is_empty()) {
         return Corpus::Reject;
     }
 
+    let number_of_addresses = config
+        .number_of_addresses()
+        .unwrap_or(input.address_generator.number_of_addresses);
+    input.set_number_of_addresses(number_of_addresses);
+
     //eprintln!("input: {input:#?}");
 
     // We use a new Env each transaction, and the prng seed needs to be set
@@ -56,16 +71,26 @@ pub fn fuzz_token(config: Config, input: Input) -> Corpus {
 
     // Do initial setup, including registering the contract.
     {
-        input.address_generator.setup_account_storage(&env);
-
         let signers = input.address_generator.generate_signers(&env);
-        let admin = &signers[0].address;
+        let admin = &signers[input.init.admin_account_index].address;
 
-        let token_contract_id = config.register_contract_init(&env, admin);
+        let Some(token_contract_id) =
+            config.register_contract_init(&env, admin, &input.init.params, &input.asset)
+        else {
+            return Corpus::Reject;
+        };
         token_contract_id_bytes = address_to_bytes(&token_contract_id);
+
+        // Trustlines are created for the asset as the token reports it,
+        // not as we asked for it.
+        let asset = config.stellar_asset(&env, &token_contract_id);
+        input
+            .address_generator
+            .setup_account_storage(&env, asset.as_ref());
     }
 
-    let mut contract_state = ContractState::init();
+    let mut contract_state = ContractState::init(input.init.admin_account_index);
+    let mut event_ledger = EventLedger::init();
     let mut current_state = CurrentState::new(
         &env,
         &config,
@@ -73,26 +98,52 @@ pub fn fuzz_token(config: Config, input: Input) -> Corpus {
         &input.address_generator,
     );
     let mut signature_nonce = 0;
+    let mut accepted_calls = RustVec::new();
 
     // Save some values that should never change
     // fixme put this in the ContractState ctor
     {
         let token_client = &current_state.token_client;
         let signers = input.address_generator.generate_signers(&env);
-        let admin = &signers[0].address;
+        let admin = &signers[input.init.admin_account_index].address;
+
+        if config.balances_in_trustlines() {
+            contract_state.set_trustlines(&signers);
+        }
 
-        // Some tokens have an initial balance > 0.
-        // e.g. CometDEX LP token needs some initial balance for the pool to be "finalized" (activated).
-        // This assumes that balance is minted to the admin and asks the contract for the initial balance.
-        let init_balance = token_client.balance(admin);
-        if init_balance > 0 {
-            contract_state.set_balance(admin, init_balance);
-            contract_state.set_sum_of_mints(init_balance);
+        if let Some(min_balance) = config.account_min_balance() {
+            contract_state.set_account_min_balances(&signers, min_balance);
+
+            // Stellar accounts hold lumens before the token exists,
+            // and no event accounts for them.
+            for signer in &signers {
+                if signer.classic_account().is_none()
+                    || !contract_state.can_hold_balance(&signer.address)
+                {
+                    continue;
+                }
+                let init_balance = token_client.balance(&signer.address);
+                contract_state.add_initial_balance(&signer.address, init_balance);
+                event_ledger.add_initial_balance(&signer.address, init_balance);
+            }
+        } else {
+            // Some tokens have an initial balance > 0.
+            // e.g. CometDEX LP token needs some initial balance for the pool to be "finalized" (activated).
+            // This assumes that balance is minted to the admin and asks the contract for the initial balance.
+            let init_balance = if contract_state.can_hold_balance(admin) {
+                token_client.balance(admin)
+            } else {
+                0
+            };
+            if init_balance > 0 {
+                contract_state.add_initial_balance(admin, init_balance);
+            }
         }
 
         contract_state.name = string_to_bytes(token_client.name());
         contract_state.symbol = string_to_bytes(token_client.symbol());
         contract_state.decimals = token_client.decimals();
+        contract_state.burnable = config.burnable();
     }
 
     for transaction in &input.transactions {
@@ -104,13 +155,17 @@ pub fn fuzz_token(config: Config, input: Input) -> Corpus {
             exec_command(
                 &command,
                 &env,
-                &token_contract_id_bytes,
                 &mut contract_state,
                 &current_state,
                 &mut signature_nonce,
+                &mut accepted_calls,
             );
         }
 
+        // Events don't survive recreating the Env,
+        // so record them before advancing time.
+        event_ledger.apply_events(&env, &current_state.token_client.address);
+
         // Advance time and begin new transaction
         {
             env = advance_time(
@@ -119,6 +174,7 @@ pub fn fuzz_token(config: Config, input: Input) -> Corpus {
                 &token_contract_id_bytes,
                 transaction.advance_ledgers,
                 &mut env_prng_seed,
+                contract_state.upgraded,
             );
             // NB: This env is reconstructed and all previous env-based objects are invalid
 
@@ -129,31 +185,9 @@ pub fn fuzz_token(config: Config, input: Input) -> Corpus {
                 &input.address_generator,
             );
 
-            // update saved allowance number after advance ledgers
-            // fixme track expiration ledger instead of asking the contract
-            {
-                let pairs = current_state
-                    .accounts
-                    .iter()
-                    .cartesian_product(current_state.accounts.iter());
-                for (signer1, signer2) in pairs {
-                    let expected_allowance =
-                        contract_state.get_allowance(&signer1.address, &signer2.address);
-                    let actual_allowance = current_state
-                        .token_client
-                        .allowance(&signer1.address, &signer2.address);
-                    if actual_allowance == 0 && expected_allowance != 0 {
-                        // Assume the allowance expired.
-                        contract_state.set_allowance(
-                            &signer1.address,
-                            &signer2.address,
-                            actual_allowance,
-                        );
-                    }
-                }
-            }
+            contract_state.expire_allowances(env.ledger().sequence());
 
-            assert_state(&contract_state, &current_state);
+            assert_state(&contract_state, &event_ledger, &current_state);
         }
     }
 
@@ -163,10 +197,10 @@ pub fn fuzz_token(config: Config, input: Input) -> Corpus {
 fn exec_command(
     command: &Command,
     env: &Env,
-    token_contract_id_bytes: &[u8],
     contract_state: &mut ContractState,
     current_state: &CurrentState,
     signature_nonce: &mut i64,
+    accepted_calls: &mut RustVec<AcceptedCall>,
 ) {
     let admin_client = &current_state.admin_client;
     let token_client = &current_state.token_client;
@@ -174,32 +208,81 @@ fn exec_command(
 
     match command {
         Command::Mint(input) => {
-            mock_auths_for_command(
+            // Declared tokens may name their mint function differently,
+            // or take its arguments in a different order.
+            let (mint_fn_name, mint_args) = admin_client.mint_invocation(
+                env,
+                &accounts[input.to_account_index].address,
+                &input.amount.0,
+            );
+            let provided = mock_auths_for_command(
                 env,
-                "mint",
+                mint_fn_name,
                 &input.auths,
+                AuthTampering {
+                    corruption: input.auth_corruption,
+                    mismatch: input.invocation_mismatch,
+                },
                 current_state,
-                token_contract_id_bytes,
                 signature_nonce,
-                (&accounts[input.to_account_index].address, input.amount.0).into_val(env),
+                mint_args,
+            );
+
+            let admin_authorized = provided.is_valid(contract_state.admin_account_index)
+                || !current_state.mint_requires_admin_auth;
+            let expect = contract_state.predict_mint(
+                &accounts[input.to_account_index].address,
+                input.amount.0,
+                admin_authorized,
             );
 
+            let events_before = token_events(env, &token_client.address).len();
+            let pre_snapshot = env.to_snapshot();
+
+            let admin_client = admin_client.set_auths(&provided.auth_entries);
             let r =
                 admin_client.try_mint(&accounts[input.to_account_index].address, &input.amount.0);
 
             verify_token_contract_result(&env, &r);
-
-            if input.amount.0 < 0 {
-                assert!(r.is_err());
+            // Tokens that let anybody mint don't check the admin's auth.
+            if current_state.mint_requires_admin_auth {
+                let admin_account_index = contract_state.admin_account_index;
+                provided.verify_tampering_rejected(
+                    env,
+                    "mint",
+                    current_state,
+                    admin_account_index,
+                    &r,
+                );
+                provided.verify_required_auths(env, current_state, admin_account_index, &r);
+                provided.record_accepted_call(env, &r, accepted_calls);
             }
-
-            // fixme We use mock_auths in Comet mint
-            /*if input.auths[0] == false {
-                assert!(r.is_err());
-            }*/
+            verify_prediction(env, "mint", expect, &r);
+            verify_failure_changed_nothing(env, "mint", &token_client.address, &r, &pre_snapshot);
 
             if let Ok(r) = r {
-                let _r = r.expect("ok");
+                r.expect("ok");
+
+                if current_state.mint_emits_standard_event {
+                    verify_events(
+                        env,
+                        "mint",
+                        &token_client.address,
+                        events_before,
+                        &[(
+                            current_state.event_topics(
+                                env,
+                                contract_state,
+                                (
+                                    symbol_short!("mint"),
+                                    &accounts[contract_state.admin_account_index].address,
+                                    &accounts[input.to_account_index].address,
+                                ),
+                            ),
+                            input.amount.0.into_val(env),
+                        )],
+                    );
+                }
 
                 contract_state
                     .add_balance(&accounts[input.to_account_index].address, input.amount.0);
@@ -208,12 +291,15 @@ fn exec_command(
             }
         }
         Command::Approve(input) => {
-            mock_auths_for_command(
+            let provided = mock_auths_for_command(
                 env,
                 "approve",
                 &input.auths,
+                AuthTampering {
+                    corruption: input.auth_corruption,
+                    mismatch: input.invocation_mismatch,
+                },
                 current_state,
-                token_contract_id_bytes,
                 signature_nonce,
                 (
                     &accounts[input.from_account_index].address,
@@ -224,6 +310,24 @@ fn exec_command(
                     .into_val(env),
             );
 
+            let expect = contract_state.predict_approve(
+                input.amount.0,
+                input.expiration_ledger,
+                provided.is_valid(input.from_account_index),
+                env.ledger().sequence(),
+                max_live_until_ledger(env),
+            );
+            let is_same_allowance = input.amount.0 > 0
+                && contract_state.has_allowance(
+                    &accounts[input.from_account_index].address,
+                    &accounts[input.spender_account_index].address,
+                    input.amount.0,
+                    input.expiration_ledger,
+                );
+
+            let events_before = token_events(env, &token_client.address).len();
+            let pre_snapshot = env.to_snapshot();
+
             let r = token_client.try_approve(
                 &accounts[input.from_account_index].address,
                 &accounts[input.spender_account_index].address,
@@ -232,32 +336,68 @@ fn exec_command(
             );
 
             verify_token_contract_result(&env, &r);
+            provided.verify_tampering_rejected(
+                env,
+                "approve",
+                current_state,
+                input.from_account_index,
+                &r,
+            );
+            provided.verify_required_auths(env, current_state, input.from_account_index, &r);
+            provided.record_accepted_call(env, &r, accepted_calls);
+            verify_prediction(env, "approve", expect, &r);
+            verify_failure_changed_nothing(
+                env,
+                "approve",
+                &token_client.address,
+                &r,
+                &pre_snapshot,
+            );
 
-            if input.amount.0 < 0 {
-                assert!(r.is_err());
-            }
+            if let Ok(r) = r {
+                r.expect("ok");
 
-            if input.auths[input.from_account_index] == false {
-                assert!(r.is_err());
-            }
+                if is_same_allowance {
+                    check_noop_did_not_write(env, "approve", &token_client.address, &pre_snapshot);
+                }
 
-            if let Ok(r) = r {
-                let _r = r.expect("ok");
+                verify_events(
+                    env,
+                    "approve",
+                    &token_client.address,
+                    events_before,
+                    &[(
+                        current_state.event_topics(
+                            env,
+                            contract_state,
+                            (
+                                symbol_short!("approve"),
+                                &accounts[input.from_account_index].address,
+                                &accounts[input.spender_account_index].address,
+                            ),
+                        ),
+                        (input.amount.0, input.expiration_ledger).into_val(env),
+                    )],
+                );
 
                 contract_state.set_allowance(
                     &accounts[input.from_account_index].address,
                     &accounts[input.spender_account_index].address,
                     input.amount.0,
+                    input.expiration_ledger,
                 );
             }
         }
         Command::TransferFrom(input) => {
-            mock_auths_for_command(
+            let provided = mock_auths_for_command(
                 env,
                 "transfer_from",
                 &input.auths,
+                AuthTampering {
+                    corruption: input.auth_corruption,
+                    mismatch: input.invocation_mismatch,
+                },
                 current_state,
-                token_contract_id_bytes,
                 signature_nonce,
                 (
                     &accounts[input.spender_account_index].address,
@@ -268,6 +408,15 @@ fn exec_command(
                     .into_val(env),
             );
 
+            let expect = contract_state.predict_transfer_from(
+                &accounts[input.spender_account_index].address,
+                &accounts[input.from_account_index].address,
+                &accounts[input.to_account_index].address,
+                input.amount.0,
+                provided.is_valid(input.spender_account_index),
+            );
+
+            let events_before = token_events(env, &token_client.address).len();
             let pre_snapshot = env.to_snapshot();
 
             let r = token_client.try_transfer_from(
@@ -278,24 +427,53 @@ fn exec_command(
             );
 
             verify_token_contract_result(&env, &r);
-
-            if input.amount.0 < 0 {
-                assert!(r.is_err());
-            }
-
-            if input.auths[input.spender_account_index] == false {
-                assert!(r.is_err());
-            }
+            provided.verify_tampering_rejected(
+                env,
+                "transfer_from",
+                current_state,
+                input.spender_account_index,
+                &r,
+            );
+            provided.verify_required_auths(env, current_state, input.spender_account_index, &r);
+            provided.record_accepted_call(env, &r, accepted_calls);
+            verify_prediction(env, "transfer_from", expect, &r);
+            verify_failure_changed_nothing(
+                env,
+                "transfer_from",
+                &token_client.address,
+                &r,
+                &pre_snapshot,
+            );
 
             if let Ok(r) = r {
-                let _r = r.expect("ok");
+                r.expect("ok");
+
+                if input.amount.0 == 0 {
+                    check_noop_did_not_write(
+                        env,
+                        "transfer_from",
+                        &token_client.address,
+                        &pre_snapshot,
+                    );
+                }
 
-                let post_snapshot = env.to_snapshot();
-                check_for_zero_allowance_bug(
-                    &current_state.token_client.address,
-                    input.amount.0,
-                    pre_snapshot,
-                    post_snapshot,
+                verify_events(
+                    env,
+                    "transfer_from",
+                    &token_client.address,
+                    events_before,
+                    &[(
+                        current_state.event_topics(
+                            env,
+                            contract_state,
+                            (
+                                symbol_short!("transfer"),
+                                &accounts[input.from_account_index].address,
+                                &accounts[input.to_account_index].address,
+                            ),
+                        ),
+                        input.amount.0.into_val(env),
+                    )],
                 );
 
                 contract_state
@@ -311,12 +489,15 @@ fn exec_command(
             }
         }
         Command::Transfer(input) => {
-            mock_auths_for_command(
+            let provided = mock_auths_for_command(
                 env,
                 "transfer",
                 &input.auths,
+                AuthTampering {
+                    corruption: input.auth_corruption,
+                    mismatch: input.invocation_mismatch,
+                },
                 current_state,
-                token_contract_id_bytes,
                 signature_nonce,
                 (
                     &accounts[input.from_account_index].address,
@@ -326,6 +507,16 @@ fn exec_command(
                     .into_val(env),
             );
 
+            let expect = contract_state.predict_transfer(
+                &accounts[input.from_account_index].address,
+                &accounts[input.to_account_index].address,
+                input.amount.0,
+                provided.is_valid(input.from_account_index),
+            );
+
+            let events_before = token_events(env, &token_client.address).len();
+            let pre_snapshot = env.to_snapshot();
+
             let r = token_client.try_transfer(
                 &accounts[input.from_account_index].address,
                 &accounts[input.to_account_index].address,
@@ -333,17 +524,50 @@ fn exec_command(
             );
 
             verify_token_contract_result(&env, &r);
+            provided.verify_tampering_rejected(
+                env,
+                "transfer",
+                current_state,
+                input.from_account_index,
+                &r,
+            );
+            provided.verify_required_auths(env, current_state, input.from_account_index, &r);
+            provided.record_accepted_call(env, &r, accepted_calls);
+            verify_prediction(env, "transfer", expect, &r);
+            verify_failure_changed_nothing(
+                env,
+                "transfer",
+                &token_client.address,
+                &r,
+                &pre_snapshot,
+            );
 
-            if input.amount.0 < 0 {
-                assert!(r.is_err());
-            }
+            if let Ok(r) = r {
+                r.expect("ok");
 
-            if input.auths[input.from_account_index] == false {
-                assert!(r.is_err());
-            }
+                let is_self_transfer = input.from_account_index == input.to_account_index;
+                if input.amount.0 == 0 || is_self_transfer {
+                    check_noop_did_not_write(env, "transfer", &token_client.address, &pre_snapshot);
+                }
 
-            if let Ok(r) = r {
-                let _r = r.expect("ok");
+                verify_events(
+                    env,
+                    "transfer",
+                    &token_client.address,
+                    events_before,
+                    &[(
+                        current_state.event_topics(
+                            env,
+                            contract_state,
+                            (
+                                symbol_short!("transfer"),
+                                &accounts[input.from_account_index].address,
+                                &accounts[input.to_account_index].address,
+                            ),
+                        ),
+                        input.amount.0.into_val(env),
+                    )],
+                );
 
                 contract_state
                     .sub_balance(&accounts[input.from_account_index].address, input.amount.0);
@@ -352,12 +576,15 @@ fn exec_command(
             }
         }
         Command::BurnFrom(input) => {
-            mock_auths_for_command(
+            let provided = mock_auths_for_command(
                 env,
                 "burn_from",
                 &input.auths,
+                AuthTampering {
+                    corruption: input.auth_corruption,
+                    mismatch: input.invocation_mismatch,
+                },
                 current_state,
-                token_contract_id_bytes,
                 signature_nonce,
                 (
                     &accounts[input.spender_account_index].address,
@@ -367,6 +594,16 @@ fn exec_command(
                     .into_val(env),
             );
 
+            let expect = contract_state.predict_burn_from(
+                &accounts[input.spender_account_index].address,
+                &accounts[input.from_account_index].address,
+                input.amount.0,
+                provided.is_valid(input.spender_account_index),
+            );
+
+            let events_before = token_events(env, &token_client.address).len();
+            let pre_snapshot = env.to_snapshot();
+
             let r = token_client.try_burn_from(
                 &accounts[input.spender_account_index].address,
                 &accounts[input.from_account_index].address,
@@ -374,17 +611,53 @@ fn exec_command(
             );
 
             verify_token_contract_result(&env, &r);
-
-            if input.amount.0 < 0 {
-                assert!(r.is_err());
-            }
-
-            if input.auths[input.spender_account_index] == false {
-                assert!(r.is_err());
-            }
+            provided.verify_tampering_rejected(
+                env,
+                "burn_from",
+                current_state,
+                input.spender_account_index,
+                &r,
+            );
+            provided.verify_required_auths(env, current_state, input.spender_account_index, &r);
+            provided.record_accepted_call(env, &r, accepted_calls);
+            verify_prediction(env, "burn_from", expect, &r);
+            verify_failure_changed_nothing(
+                env,
+                "burn_from",
+                &token_client.address,
+                &r,
+                &pre_snapshot,
+            );
 
             if let Ok(r) = r {
-                let _r = r.expect("ok");
+                r.expect("ok");
+
+                if input.amount.0 == 0 {
+                    check_noop_did_not_write(
+                        env,
+                        "burn_from",
+                        &token_client.address,
+                        &pre_snapshot,
+                    );
+                }
+
+                verify_events(
+                    env,
+                    "burn_from",
+                    &token_client.address,
+                    events_before,
+                    &[(
+                        current_state.event_topics(
+                            env,
+                            contract_state,
+                            (
+                                symbol_short!("burn"),
+                                &accounts[input.from_account_index].address,
+                            ),
+                        ),
+                        input.amount.0.into_val(env),
+                    )],
+                );
 
                 contract_state
                     .sub_balance(&accounts[input.from_account_index].address, input.amount.0);
@@ -400,31 +673,68 @@ fn exec_command(
             }
         }
         Command::Burn(input) => {
-            mock_auths_for_command(
+            let provided = mock_auths_for_command(
                 env,
                 "burn",
                 &input.auths,
+                AuthTampering {
+                    corruption: input.auth_corruption,
+                    mismatch: input.invocation_mismatch,
+                },
                 current_state,
-                token_contract_id_bytes,
                 signature_nonce,
                 (&accounts[input.from_account_index].address, input.amount.0).into_val(env),
             );
 
+            let expect = contract_state.predict_burn(
+                &accounts[input.from_account_index].address,
+                input.amount.0,
+                provided.is_valid(input.from_account_index),
+            );
+
+            let events_before = token_events(env, &token_client.address).len();
+            let pre_snapshot = env.to_snapshot();
+
             let r =
                 token_client.try_burn(&accounts[input.from_account_index].address, &input.amount.0);
 
             verify_token_contract_result(&env, &r);
+            provided.verify_tampering_rejected(
+                env,
+                "burn",
+                current_state,
+                input.from_account_index,
+                &r,
+            );
+            provided.verify_required_auths(env, current_state, input.from_account_index, &r);
+            provided.record_accepted_call(env, &r, accepted_calls);
+            verify_prediction(env, "burn", expect, &r);
+            verify_failure_changed_nothing(env, "burn", &token_client.address, &r, &pre_snapshot);
 
-            if input.amount.0 < 0 {
-                assert!(r.is_err());
-            }
+            if let Ok(r) = r {
+                r.expect("ok");
 
-            if input.auths[input.from_account_index] == false {
-                assert!(r.is_err());
-            }
+                if input.amount.0 == 0 {
+                    check_noop_did_not_write(env, "burn", &token_client.address, &pre_snapshot);
+                }
 
-            if let Ok(r) = r {
-                let _r = r.expect("ok");
+                verify_events(
+                    env,
+                    "burn",
+                    &token_client.address,
+                    events_before,
+                    &[(
+                        current_state.event_topics(
+                            env,
+                            contract_state,
+                            (
+                                symbol_short!("burn"),
+                                &accounts[input.from_account_index].address,
+                            ),
+                        ),
+                        input.amount.0.into_val(env),
+                    )],
+                );
 
                 contract_state
                     .sub_balance(&accounts[input.from_account_index].address, input.amount.0);
@@ -433,42 +743,416 @@ fn exec_command(
                     contract_state.sum_of_burns.clone() + &BigInt::from(input.amount.0);
             }
         }
+        Command::Balance(input) => {
+            let id = &accounts[input.id_account_index].address;
+
+            let pre_snapshot = env.to_snapshot();
+            let r = token_client.try_balance(id);
+            let post_snapshot = env.to_snapshot();
+
+            check_accessor_is_read_only("balance", current_state, &pre_snapshot, &post_snapshot);
+
+            if contract_state.can_hold_balance(id) {
+                let balance = r.expect("ok").expect("ok");
+                assert_eq!(balance, contract_state.get_balance(id));
+            } else {
+                assert!(r.is_err(), "balance of an account without a trustline");
+            }
+        }
+        Command::Allowance(input) => {
+            let pre_snapshot = env.to_snapshot();
+            let allowance = token_client.allowance(
+                &accounts[input.from_account_index].address,
+                &accounts[input.spender_account_index].address,
+            );
+            let post_snapshot = env.to_snapshot();
+
+            check_accessor_is_read_only("allowance", current_state, &pre_snapshot, &post_snapshot);
+
+            assert_eq!(
+                allowance,
+                contract_state.get_allowance(
+                    &accounts[input.from_account_index].address,
+                    &accounts[input.spender_account_index].address,
+                )
+            );
+        }
+        Command::Decimals => {
+            let pre_snapshot = env.to_snapshot();
+            let decimals = token_client.decimals();
+            let post_snapshot = env.to_snapshot();
+
+            check_accessor_is_read_only("decimals", current_state, &pre_snapshot, &post_snapshot);
+
+            assert_eq!(decimals, contract_state.decimals);
+        }
+        Command::Name => {
+            let pre_snapshot = env.to_snapshot();
+            let name = token_client.name();
+            let post_snapshot = env.to_snapshot();
+
+            check_accessor_is_read_only("name", current_state, &pre_snapshot, &post_snapshot);
+
+            assert_eq!(string_to_bytes(name), contract_state.name);
+        }
+        Command::Symbol => {
+            let pre_snapshot = env.to_snapshot();
+            let symbol = token_client.symbol();
+            let post_snapshot = env.to_snapshot();
+
+            check_accessor_is_read_only("symbol", current_state, &pre_snapshot, &post_snapshot);
+
+            assert_eq!(string_to_bytes(symbol), contract_state.symbol);
+        }
+        Command::Replay(input) => {
+            if accepted_calls.is_empty() {
+                return;
+            }
+            let call = &accepted_calls[input.call_index % accepted_calls.len()];
+
+            // The Env may have been recreated since the call was accepted,
+            // and contract addresses need their account contracts
+            // for their entries to be rejected for the right reason.
+            for signer in accounts {
+                let sc_address = ScAddress::try_from(signer.address.clone()).unwrap();
+                let signed = call.auth_entries.iter().any(|entry| {
+                    matches!(
+                        &entry.credentials,
+                        SorobanCredentials::Address(credentials) if credentials.address == sc_address
+                    )
+                });
+                if signed {
+                    signer.register_account_contract(env);
+                }
+            }
+
+            env.set_auths(&call.auth_entries);
+
+            let fn_name = call.invocation.function_name.to_utf8_string().unwrap();
+            let args = soroban_sdk::Vec::<Val>::try_from_val(
+                env,
+                &ScVal::Vec(Some(call.invocation.args.to_vec().try_into().unwrap())),
+            )
+            .unwrap();
+
+            let pre_snapshot = env.to_snapshot();
+
+            // The `try_` client methods narrow the host's auth errors
+            // to the same error as any other failure,
+            // so call the host directly to report why the call failed.
+            let r: UnitResult = match env.host().call(
+                token_client.address.to_object(),
+                Symbol::new(env, &fn_name).to_symbol_val(),
+                args.to_object(),
+            ) {
+                Ok(_) => Ok(Ok(())),
+                Err(e) => Err(Ok(e.error)),
+            };
+
+            verify_token_contract_result(env, &r);
+            match &r {
+                Ok(_) => {
+                    let msg = format!("{fn_name} accepted replayed auth entries");
+                    eprintln!("{msg}");
+                    eprintln!("{:#?}", call.auth_entries);
+                    print_diagnostics(env);
+                    panic!("{msg}");
+                }
+                Err(Ok(e)) if e.is_type(ScErrorType::Auth) => {}
+                // The token may check its own state before the auth,
+                // e.g. whether it is paused, and reject the call for that.
+                Err(e) => {
+                    eprintln!(
+                        "{fn_name} rejected replayed auth entries with a non-auth error: {e:?}"
+                    );
+                }
+            }
+            verify_failure_changed_nothing(env, &fn_name, &token_client.address, &r, &pre_snapshot);
+        }
+        Command::SetAdmin(input) => {
+            let admin_account_index = contract_state.admin_account_index;
+            let new_admin = &accounts[input.new_admin_account_index].address;
+
+            let provided = mock_auths_for_command(
+                env,
+                "set_admin",
+                &input.auths,
+                AuthTampering::default(),
+                current_state,
+                signature_nonce,
+                (new_admin,).into_val(env),
+            );
+
+            let expect = contract_state.predict_admin_call(provided.is_valid(admin_account_index));
+
+            let events_before = token_events(env, &token_client.address).len();
+            let pre_snapshot = env.to_snapshot();
+
+            let admin_client = admin_client.set_auths(&provided.auth_entries);
+            let Some(r) = admin_client.try_set_admin(new_admin) else {
+                return;
+            };
+
+            verify_token_contract_result(env, &r);
+            provided.verify_required_auths(env, current_state, admin_account_index, &r);
+            provided.record_accepted_call(env, &r, accepted_calls);
+            verify_prediction(env, "set_admin", expect, &r);
+            verify_failure_changed_nothing(
+                env,
+                "set_admin",
+                &token_client.address,
+                &r,
+                &pre_snapshot,
+            );
+
+            if let Ok(r) = r {
+                r.expect("ok");
+
+                verify_events(
+                    env,
+                    "set_admin",
+                    &token_client.address,
+                    events_before,
+                    &[(
+                        current_state.event_topics(
+                            env,
+                            contract_state,
+                            (
+                                Symbol::new(env, "set_admin"),
+                                &accounts[admin_account_index].address,
+                            ),
+                        ),
+                        new_admin.into_val(env),
+                    )],
+                );
+
+                contract_state.admin_account_index = input.new_admin_account_index;
+            }
+        }
+        Command::Clawback(input) => {
+            let admin_account_index = contract_state.admin_account_index;
+            let from = &accounts[input.from_account_index].address;
+
+            let provided = mock_auths_for_command(
+                env,
+                "clawback",
+                &input.auths,
+                AuthTampering::default(),
+                current_state,
+                signature_nonce,
+                (from, input.amount.0).into_val(env),
+            );
+
+            let expect = contract_state.predict_clawback(
+                from,
+                input.amount.0,
+                provided.is_valid(admin_account_index),
+            );
+
+            let events_before = token_events(env, &token_client.address).len();
+            let pre_snapshot = env.to_snapshot();
+
+            let admin_client = admin_client.set_auths(&provided.auth_entries);
+            let Some(r) = admin_client.try_clawback(from, &input.amount.0) else {
+                return;
+            };
+
+            verify_token_contract_result(env, &r);
+            provided.verify_required_auths(env, current_state, admin_account_index, &r);
+            provided.record_accepted_call(env, &r, accepted_calls);
+            verify_prediction(env, "clawback", expect, &r);
+            verify_failure_changed_nothing(
+                env,
+                "clawback",
+                &token_client.address,
+                &r,
+                &pre_snapshot,
+            );
+
+            if let Ok(r) = r {
+                r.expect("ok");
+
+                verify_events(
+                    env,
+                    "clawback",
+                    &token_client.address,
+                    events_before,
+                    &[(
+                        current_state.event_topics(
+                            env,
+                            contract_state,
+                            (
+                                symbol_short!("clawback"),
+                                &accounts[admin_account_index].address,
+                                from,
+                            ),
+                        ),
+                        input.amount.0.into_val(env),
+                    )],
+                );
+
+                contract_state.sub_balance(from, input.amount.0);
+                contract_state.sum_of_clawbacks =
+                    contract_state.sum_of_clawbacks.clone() + &BigInt::from(input.amount.0);
+            }
+        }
+        Command::SetAuthorized(input) => {
+            let admin_account_index = contract_state.admin_account_index;
+            let id = &accounts[input.id_account_index].address;
+
+            let provided = mock_auths_for_command(
+                env,
+                "set_authorized",
+                &input.auths,
+                AuthTampering::default(),
+                current_state,
+                signature_nonce,
+                (id, input.authorize).into_val(env),
+            );
+
+            let expect =
+                contract_state.predict_set_authorized(id, provided.is_valid(admin_account_index));
+
+            let events_before = token_events(env, &token_client.address).len();
+            let pre_snapshot = env.to_snapshot();
+
+            let admin_client = admin_client.set_auths(&provided.auth_entries);
+            let Some(r) = admin_client.try_set_authorized(id, &input.authorize) else {
+                return;
+            };
+
+            verify_token_contract_result(env, &r);
+            provided.verify_required_auths(env, current_state, admin_account_index, &r);
+            provided.record_accepted_call(env, &r, accepted_calls);
+            verify_prediction(env, "set_authorized", expect, &r);
+            verify_failure_changed_nothing(
+                env,
+                "set_authorized",
+                &token_client.address,
+                &r,
+                &pre_snapshot,
+            );
+
+            if let Ok(r) = r {
+                r.expect("ok");
+
+                verify_events(
+                    env,
+                    "set_authorized",
+                    &token_client.address,
+                    events_before,
+                    &[(
+                        current_state.event_topics(
+                            env,
+                            contract_state,
+                            (
+                                Symbol::new(env, "set_authorized"),
+                                &accounts[admin_account_index].address,
+                                id,
+                            ),
+                        ),
+                        input.authorize.into_val(env),
+                    )],
+                );
+
+                contract_state.set_holder_authorized(id, input.authorize);
+            }
+        }
+        Command::Authorized(input) => {
+            let id = &accounts[input.id_account_index].address;
+
+            let pre_snapshot = env.to_snapshot();
+            let Some(r) = admin_client.try_authorized(id) else {
+                return;
+            };
+            let post_snapshot = env.to_snapshot();
+
+            check_accessor_is_read_only("authorized", current_state, &pre_snapshot, &post_snapshot);
+
+            // The native lumens token considers every account authorized,
+            // even ones that don't exist.
+            if contract_state.lacks_trustline(id) {
+                assert!(r.is_err(), "authorized of an account without a trustline");
+            } else {
+                let authorized = r.expect("ok").expect("ok");
+                assert_eq!(authorized, contract_state.is_holder_authorized(id));
+            }
+        }
+        Command::Reinitialize(input) => {
+            let admin = &accounts[input.admin_account_index].address;
+
+            let pre_snapshot = env.to_snapshot();
+
+            // Mock every auth, so only the token's own
+            // check that it is already initialized can stop this.
+            let prev_auth_manager = env.host().snapshot_auth_manager().unwrap();
+            env.mock_all_auths();
+            let r = current_state.config.try_reinitialize(
+                env,
+                &token_client.address,
+                admin,
+                &input.params,
+            );
+            env.host().set_auth_manager(prev_auth_manager).unwrap();
+
+            let Some(r) = r else {
+                return;
+            };
+
+            // Tokens commonly panic here, so this doesn't
+            // go through `verify_token_contract_result`.
+            verify_prediction(env, "initialize", Expect::Failure, &r);
+            verify_failure_changed_nothing(
+                env,
+                "initialize",
+                &token_client.address,
+                &r,
+                &pre_snapshot,
+            );
+        }
+        Command::Upgrade => {
+            // Contracts can't be swapped out from under a running transaction,
+            // so this is done when the `Env` is recreated, by `advance_time`.
+            // Every `assert_state` after that checks the upgraded token
+            // against the state left by the original.
+            contract_state.upgraded = true;
+        }
         Command::ApproveAndTransferFrom(input) => {
             exec_command(
                 &Command::Approve(input.to_approve_input()),
                 env,
-                token_contract_id_bytes,
                 contract_state,
                 current_state,
                 signature_nonce,
+                accepted_calls,
             );
 
             exec_command(
                 &Command::TransferFrom(input.to_transfer_from_input()),
                 env,
-                token_contract_id_bytes,
                 contract_state,
                 current_state,
                 signature_nonce,
+                accepted_calls,
             );
         }
         Command::ApproveAndBurnFrom(input) => {
             exec_command(
                 &Command::Approve(input.to_approve_input()),
                 env,
-                token_contract_id_bytes,
                 contract_state,
                 current_state,
                 signature_nonce,
+                accepted_calls,
             );
 
             exec_command(
                 &Command::BurnFrom(input.to_burn_from_input()),
                 env,
-                token_contract_id_bytes,
                 contract_state,
                 current_state,
                 signature_nonce,
+                accepted_calls,
             );
         }
     }
@@ -493,29 +1177,59 @@ pub struct ContractState {
     name: RustVec<u8>,
     symbol: RustVec<u8>,
     decimals: u32,
+    /// Whether the token can be burned at all.
+    burnable: bool,
     balances: BTreeMap<RustVec<u8>, i128>,
-    allowances: BTreeMap<(RustVec<u8>, RustVec<u8>), i128>, // (from, spender)
+    allowances: BTreeMap<(RustVec<u8>, RustVec<u8>), (i128, u32)>, // (from, spender) -> (amount, expiration_ledger)
     sum_of_mints: BigInt,
     sum_of_burns: BigInt,
+    sum_of_clawbacks: BigInt,
+    /// Holders the admin has deauthorized.
+    deauthorized: BTreeSet<RustVec<u8>>,
+    /// The trustlines of account holders, or `None` if they have none,
+    /// for tokens that keep account balances in trustlines.
+    trustlines: BTreeMap<RustVec<u8>, Option<Trustline>>,
+    /// The minimum balances of account holders, or `None` if they don't exist,
+    /// for tokens that keep account balances in their account entries.
+    account_min_balances: BTreeMap<RustVec<u8>, Option<i128>>,
+    /// The most each holder's balance can be,
+    /// for tokens where we know how every holder's balance is stored.
+    balance_limits: BTreeMap<RustVec<u8>, i128>,
+    /// The index of the account that is currently the admin.
+    admin_account_index: usize,
+    /// Whether the token has been upgraded to its "v2" implementation,
+    /// which takes effect from the next transaction.
+    upgraded: bool,
 }
 
 impl ContractState {
-    fn init() -> Self {
+    fn init(admin_account_index: usize) -> Self {
         ContractState {
             name: Vec::<u8>::new(),
             symbol: Vec::<u8>::new(),
             decimals: 0,
+            burnable: true,
             balances: BTreeMap::default(),
             allowances: BTreeMap::default(),
             sum_of_mints: BigInt::default(),
             sum_of_burns: BigInt::default(),
+            sum_of_clawbacks: BigInt::default(),
+            deauthorized: BTreeSet::default(),
+            trustlines: BTreeMap::default(),
+            account_min_balances: BTreeMap::default(),
+            balance_limits: BTreeMap::default(),
+            admin_account_index,
+            upgraded: false,
         }
     }
 
-    fn set_sum_of_mints(&mut self, amount: i128) {
+    /// Record a balance the token started with,
+    /// which counts towards the sum of mints.
+    fn add_initial_balance(&mut self, addr: &Address, amount: i128) {
         assert!(amount >= 0);
 
-        self.sum_of_mints = BigInt::from(amount);
+        self.set_balance(addr, amount);
+        self.sum_of_mints += BigInt::from(amount);
     }
 
     fn set_balance(&mut self, addr: &Address, new_balance: i128) {
@@ -546,11 +1260,130 @@ impl ContractState {
         self.balances.insert(addr_bytes, new_balance);
     }
 
-    fn set_allowance(&mut self, from: &Address, spender: &Address, amount: i128) {
+    /// Whether `addr` may hold, send and receive tokens.
+    ///
+    /// Holders are authorized until the admin deauthorizes them.
+    fn is_holder_authorized(&self, addr: &Address) -> bool {
+        !self.deauthorized.contains(&address_to_bytes(addr))
+    }
+
+    fn set_holder_authorized(&mut self, addr: &Address, authorize: bool) {
+        let addr_bytes = address_to_bytes(addr);
+        if authorize {
+            self.deauthorized.remove(&addr_bytes);
+        } else {
+            self.deauthorized.insert(addr_bytes);
+        }
+    }
+
+    /// Record the trustlines of account holders,
+    /// whose flags also decide whether they start out authorized,
+    /// and whose limits are the most their balances can be.
+    ///
+    /// Contract holders' balances are `i128`s in contract data.
+    fn set_trustlines(&mut self, signers: &[TestSigner]) {
+        for signer in signers {
+            let Some(account) = signer.classic_account() else {
+                self.set_balance_limit(&signer.address, i128::MAX);
+                continue;
+            };
+            let trustline = account.trustline().cloned();
+            if let Some(trustline) = &trustline {
+                self.set_holder_authorized(&signer.address, trustline.authorized);
+                self.set_balance_limit(&signer.address, trustline.limit as i128);
+            }
+            self.trustlines
+                .insert(address_to_bytes(&signer.address), trustline);
+        }
+    }
+
+    /// Record which account holders exist, and their minimum balance,
+    /// for tokens that keep account balances in their account entries.
+    ///
+    /// Account balances are `i64`s, and contract holders'
+    /// balances are `i128`s in contract data.
+    fn set_account_min_balances(&mut self, signers: &[TestSigner], min_balance: i64) {
+        for signer in signers {
+            let Some(account) = signer.classic_account() else {
+                self.set_balance_limit(&signer.address, i128::MAX);
+                continue;
+            };
+            self.set_balance_limit(&signer.address, i64::MAX as i128);
+            self.account_min_balances.insert(
+                address_to_bytes(&signer.address),
+                account.exists.then_some(min_balance as i128),
+            );
+        }
+    }
+
+    fn trustline(&self, addr: &Address) -> Option<&Trustline> {
+        self.trustlines
+            .get(&address_to_bytes(addr))
+            .and_then(|trustline| trustline.as_ref())
+    }
+
+    fn lacks_trustline(&self, addr: &Address) -> bool {
+        matches!(self.trustlines.get(&address_to_bytes(addr)), Some(None))
+    }
+
+    fn account_min_balance(&self, addr: &Address) -> Option<i128> {
+        self.account_min_balances
+            .get(&address_to_bytes(addr))
+            .copied()
+            .flatten()
+    }
+
+    /// Whether `addr` can have a balance at all.
+    ///
+    /// Accounts without a trustline can't, nor can accounts that don't exist
+    /// when balances are kept in account entries,
+    /// and even reading their balance fails.
+    fn can_hold_balance(&self, addr: &Address) -> bool {
+        !self.lacks_trustline(addr)
+            && !matches!(
+                self.account_min_balances.get(&address_to_bytes(addr)),
+                Some(None)
+            )
+    }
+
+    /// How much of its balance `addr` can spend,
+    /// which for accounts holding lumens excludes their minimum balance.
+    fn spendable_balance(&self, addr: &Address) -> i128 {
+        self.get_balance(addr) - self.account_min_balance(addr).unwrap_or(0)
+    }
+
+    fn set_balance_limit(&mut self, addr: &Address, limit: i128) {
+        self.balance_limits.insert(address_to_bytes(addr), limit);
+    }
+
+    fn balance_limit(&self, addr: &Address) -> Option<i128> {
+        self.balance_limits.get(&address_to_bytes(addr)).copied()
+    }
+
+    /// Whether receiving `amount` would take `to` over
+    /// the most its balance can be, if we know that.
+    fn exceeds_balance_limit(&self, to: &Address, amount: i128) -> bool {
+        let Some(limit) = self.balance_limit(to) else {
+            return false;
+        };
+        match self.get_balance(to).checked_add(amount) {
+            Some(new_balance) => new_balance > limit,
+            None => true,
+        }
+    }
+
+    fn set_allowance(
+        &mut self,
+        from: &Address,
+        spender: &Address,
+        amount: i128,
+        expiration_ledger: u32,
+    ) {
         assert!(amount >= 0);
         let from_bytes = address_to_bytes(from);
         let spender_bytes = address_to_bytes(spender);
-        self.allowances.insert((from_bytes, spender_bytes), amount);
+        self.allowances
+            .insert((from_bytes, spender_bytes), (amount, expiration_ledger));
     }
 
     fn get_allowance(&self, from: &Address, spender: &Address) -> i128 {
@@ -558,30 +1391,346 @@ impl ContractState {
         let spender_bytes = address_to_bytes(spender);
         self.allowances
             .get(&(from_bytes, spender_bytes))
-            .copied()
+            .map(|(amount, _)| *amount)
             .unwrap_or(0)
     }
 
+    /// Whether `from` has already approved exactly this allowance for `spender`.
+    fn has_allowance(
+        &self,
+        from: &Address,
+        spender: &Address,
+        amount: i128,
+        expiration_ledger: u32,
+    ) -> bool {
+        let from_bytes = address_to_bytes(from);
+        let spender_bytes = address_to_bytes(spender);
+        self.allowances.get(&(from_bytes, spender_bytes)) == Some(&(amount, expiration_ledger))
+    }
+
+    /// Spending an allowance doesn't change its expiration.
     fn sub_allowance(&mut self, from: &Address, spender: &Address, amount: i128) {
         let allowance = self.get_allowance(from, spender);
         let new_allowance = allowance.checked_sub(amount).expect("overflow");
         assert!(new_allowance >= 0);
-        self.set_allowance(from, spender, new_allowance);
+
+        let from_bytes = address_to_bytes(from);
+        let spender_bytes = address_to_bytes(spender);
+        if let Some((amount, _)) = self.allowances.get_mut(&(from_bytes, spender_bytes)) {
+            *amount = new_allowance;
+        }
+    }
+
+    /// Zero every allowance whose expiration ledger is before `ledger`.
+    ///
+    /// Called each time time advances. Allowances are still usable
+    /// on their expiration ledger.
+    fn expire_allowances(&mut self, ledger: u32) {
+        for (amount, expiration_ledger) in self.allowances.values_mut() {
+            if *expiration_ledger < ledger {
+                *amount = 0;
+            }
+        }
+    }
+
+    /// Whether we know that `to` can receive `amount`.
+    ///
+    /// Some tokens, like the Stellar Asset Contract, store the balances
+    /// of some holders as `i64` and reject amounts that don't fit.
+    /// Unless we know how `to`'s balance is stored, we don't
+    /// make predictions about balances that large.
+    fn can_receive(&self, to: &Address, amount: i128) -> bool {
+        if self.balance_limit(to).is_some() {
+            return !self.exceeds_balance_limit(to, amount);
+        }
+
+        match self.get_balance(to).checked_add(amount) {
+            Some(new_balance) => new_balance <= i64::MAX as i128,
+            None => false,
+        }
+    }
+
+    /// `admin_authorized` should be `true` for tokens
+    /// that don't require the admin's auth to mint.
+    fn predict_mint(&self, to: &Address, amount: i128, admin_authorized: bool) -> Expect {
+        if amount < 0 || !admin_authorized {
+            return Expect::Failure;
+        }
+
+        if !self.can_hold_balance(to) || !self.is_holder_authorized(to) {
+            return Expect::Failure;
+        }
+
+        if self.exceeds_balance_limit(to, amount) {
+            return Expect::Failure;
+        }
+
+        // Minting is token-specific,
+        // and we don't know what the admin requires.
+        Expect::Unknown
+    }
+
+    /// Predict an admin call that has no preconditions
+    /// other than the admin's auth, like `set_admin`.
+    fn predict_admin_call(&self, admin_authorized: bool) -> Expect {
+        if !admin_authorized {
+            return Expect::Failure;
+        }
+
+        // Like minting, we don't know what else the token requires.
+        Expect::Unknown
+    }
+
+    fn predict_set_authorized(&self, id: &Address, admin_authorized: bool) -> Expect {
+        if !self.can_hold_balance(id) {
+            return Expect::Failure;
+        }
+
+        self.predict_admin_call(admin_authorized)
+    }
+
+    fn predict_clawback(&self, from: &Address, amount: i128, admin_authorized: bool) -> Expect {
+        if amount < 0 || !admin_authorized {
+            return Expect::Failure;
+        }
+
+        if !self.can_hold_balance(from) {
+            return Expect::Failure;
+        }
+
+        if let Some(trustline) = self.trustline(from) {
+            if !trustline.clawback_enabled {
+                return Expect::Failure;
+            }
+        }
+
+        // Clawback doesn't require `from` to be authorized.
+        if self.get_balance(from) < amount {
+            return Expect::Failure;
+        }
+
+        // Whether a holder's balance can be clawed back is token-specific,
+        // e.g. the Stellar Asset Contract requires the issuer to enable it.
+        Expect::Unknown
+    }
+
+    fn predict_approve(
+        &self,
+        amount: i128,
+        expiration_ledger: u32,
+        authorized: bool,
+        curr_ledger: u32,
+        max_live_until_ledger: u32,
+    ) -> Expect {
+        if amount < 0 || !authorized {
+            return Expect::Failure;
+        }
+
+        if amount > 0 && expiration_ledger < curr_ledger {
+            return Expect::Failure;
+        }
+
+        // Allowances in temporary storage can't live this long,
+        // but tokens may clamp the expiration or use other storage.
+        if expiration_ledger > max_live_until_ledger {
+            return Expect::Unknown;
+        }
+
+        Expect::Success
+    }
+
+    fn predict_transfer(
+        &self,
+        from: &Address,
+        to: &Address,
+        amount: i128,
+        authorized: bool,
+    ) -> Expect {
+        if amount < 0 || !authorized {
+            return Expect::Failure;
+        }
+
+        if !self.can_hold_balance(from) || !self.can_hold_balance(to) {
+            return Expect::Failure;
+        }
+
+        if !self.is_holder_authorized(from) || !self.is_holder_authorized(to) {
+            return Expect::Failure;
+        }
+
+        if self.spendable_balance(from) < amount {
+            return Expect::Failure;
+        }
+
+        if from != to && self.exceeds_balance_limit(to, amount) {
+            return Expect::Failure;
+        }
+
+        if from != to && !self.can_receive(to, amount) {
+            return Expect::Unknown;
+        }
+
+        Expect::Success
+    }
+
+    fn predict_transfer_from(
+        &self,
+        spender: &Address,
+        from: &Address,
+        to: &Address,
+        amount: i128,
+        authorized: bool,
+    ) -> Expect {
+        if amount < 0 || !authorized {
+            return Expect::Failure;
+        }
+
+        if self.get_allowance(from, spender) < amount {
+            return Expect::Failure;
+        }
+
+        self.predict_transfer(from, to, amount, authorized)
+    }
+
+    fn predict_burn(&self, from: &Address, amount: i128, authorized: bool) -> Expect {
+        if amount < 0 || !authorized || !self.burnable {
+            return Expect::Failure;
+        }
+
+        if !self.can_hold_balance(from) || !self.is_holder_authorized(from) {
+            return Expect::Failure;
+        }
+
+        if self.spendable_balance(from) < amount {
+            return Expect::Failure;
+        }
+
+        Expect::Success
+    }
+
+    fn predict_burn_from(
+        &self,
+        spender: &Address,
+        from: &Address,
+        amount: i128,
+        authorized: bool,
+    ) -> Expect {
+        if amount < 0 || !authorized {
+            return Expect::Failure;
+        }
+
+        if self.get_allowance(from, spender) < amount {
+            return Expect::Failure;
+        }
+
+        self.predict_burn(from, amount, authorized)
+    }
+}
+
+/// What [`ContractState`] predicts about the outcome of a call.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Expect {
+    /// The call must succeed.
+    Success,
+    /// The call must fail.
+    Failure,
+    /// The model doesn't know enough to say.
+    Unknown,
+}
+
+/// Balances reconstructed only from the events emitted by the token,
+/// the way an off-chain indexer would see them.
+///
+/// Like [`ContractState`] this persists across transactions,
+/// but events do not, so they must be applied before every
+/// `Env` is thrown away.
+pub struct EventLedger {
+    balances: BTreeMap<ScAddress, BigInt>,
+}
+
+impl EventLedger {
+    fn init() -> Self {
+        EventLedger {
+            balances: BTreeMap::default(),
+        }
+    }
+
+    fn get_balance(&self, addr: &Address) -> BigInt {
+        let addr = ScAddress::try_from(addr).unwrap();
+        self.balances.get(&addr).cloned().unwrap_or_default()
+    }
+
+    /// Apply the `mint`, `transfer`, `burn` and `clawback` events
+    /// emitted by the token in this `Env`.
+    fn apply_events(&mut self, env: &Env, token_contract_id: &Address) {
+        for (topics, data) in token_events(env, token_contract_id) {
+            let name = match topics.first() {
+                Some(ScVal::Symbol(name)) => name.to_utf8_string_lossy(),
+                _ => continue,
+            };
+            let address = |i: usize| -> ScAddress {
+                match topics.get(i) {
+                    Some(ScVal::Address(addr)) => addr.clone(),
+                    _ => panic!("malformed {name} event topics: {topics:?}"),
+                }
+            };
+            let amount = || -> BigInt {
+                let amount = i128::try_from(data.clone())
+                    .unwrap_or_else(|_| panic!("malformed {name} event data: {data:?}"));
+                BigInt::from(amount)
+            };
+
+            match name.as_str() {
+                "mint" => {
+                    self.credit(address(2), amount());
+                }
+                "transfer" => {
+                    self.debit(address(1), amount());
+                    self.credit(address(2), amount());
+                }
+                "burn" => {
+                    self.debit(address(1), amount());
+                }
+                "clawback" => {
+                    self.debit(address(2), amount());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Record a balance the token started with,
+    /// for which it emitted no event.
+    fn add_initial_balance(&mut self, addr: &Address, amount: i128) {
+        self.credit(ScAddress::try_from(addr).unwrap(), BigInt::from(amount));
+    }
+
+    fn credit(&mut self, addr: ScAddress, amount: BigInt) {
+        *self.balances.entry(addr).or_default() += amount;
+    }
+
+    fn debit(&mut self, addr: ScAddress, amount: BigInt) {
+        *self.balances.entry(addr).or_default() -= amount;
     }
 }
 
 /// State that dependso on the `Env` and is reconstructed
 /// every transaction.
 struct CurrentState<'a> {
+    config: &'a Config,
     accounts: Vec<TestSigner>,
     admin_client: Box<dyn TokenAdminClient<'a> + 'a>,
     token_client: Client<'a>,
+    mint_emits_standard_event: bool,
+    mint_requires_admin_auth: bool,
+    events_include_name_topic: bool,
+    accessors_may_extend_ttl: bool,
 }
 
 impl<'a> CurrentState<'a> {
     fn new(
         env: &Env,
-        config: &Config,
+        config: &'a Config,
         token_contract_id_bytes: &[u8],
         address_generator: &AddressGenerator,
     ) -> Self {
@@ -593,26 +1742,65 @@ impl<'a> CurrentState<'a> {
         let accounts = address_generator.generate_signers(env);
 
         CurrentState {
+            config,
             accounts,
             admin_client,
             token_client,
+            mint_emits_standard_event: config.mint_emits_standard_event(),
+            mint_requires_admin_auth: config.mint_requires_admin_auth(),
+            events_include_name_topic: config.events_include_name_topic(),
+            accessors_may_extend_ttl: config.accessors_may_extend_ttl(),
         }
     }
+
+    /// The topics we expect a token event to have.
+    fn event_topics(
+        &self,
+        env: &Env,
+        contract_state: &ContractState,
+        topics: impl IntoVal<Env, soroban_sdk::Vec<Val>>,
+    ) -> soroban_sdk::Vec<Val> {
+        let mut topics = topics.into_val(env);
+        if self.events_include_name_topic {
+            topics.push_back(String::from_bytes(env, &contract_state.name).into_val(env));
+        }
+        topics
+    }
 }
 
-fn assert_state(contract: &ContractState, current: &CurrentState) {
+fn assert_state(contract: &ContractState, events: &EventLedger, current: &CurrentState) {
     let token_client = &current.token_client;
 
     assert!(contract.name.eq(&string_to_bytes(token_client.name())));
     assert!(contract.symbol.eq(&string_to_bytes(token_client.symbol())));
     assert_eq!(contract.decimals, token_client.decimals());
 
+    if let Some(r) = current.admin_client.try_admin() {
+        assert_eq!(
+            r.expect("ok").expect("ok"),
+            current.accounts[contract.admin_account_index].address,
+            "admin differs from the fuzzer's accounting",
+        );
+    }
+
     for signer in &current.accounts {
+        if !contract.can_hold_balance(&signer.address) {
+            assert!(token_client.try_balance(&signer.address).is_err());
+            continue;
+        }
+
         assert_eq!(
             contract.get_balance(&signer.address),
             token_client.balance(&signer.address)
         );
-        assert!(token_client.balance(&signer.address) >= 0)
+        assert!(token_client.balance(&signer.address) >= 0);
+
+        if let Some(r) = current.admin_client.try_authorized(&signer.address) {
+            assert_eq!(
+                contract.is_holder_authorized(&signer.address),
+                r.expect("ok").expect("ok")
+            );
+        }
     }
 
     let pairs = current
@@ -627,14 +1815,31 @@ fn assert_state(contract: &ContractState, current: &CurrentState) {
         );
     }
 
-    let sum_of_balances_0 = &contract.sum_of_mints - &contract.sum_of_burns;
+    let sum_of_balances_0 =
+        &contract.sum_of_mints - &contract.sum_of_burns - &contract.sum_of_clawbacks;
     let sum_of_balances_1 = current
         .accounts
         .iter()
+        .filter(|a| contract.can_hold_balance(&a.address))
         .map(|a| BigInt::from(token_client.balance(&a.address)))
         .sum();
 
     assert_eq!(sum_of_balances_0, sum_of_balances_1);
+
+    // Without standard mint events the token's
+    // balances can't be reconstructed from its events.
+    if current.mint_emits_standard_event {
+        for signer in &current.accounts {
+            if !contract.can_hold_balance(&signer.address) {
+                continue;
+            }
+            assert_eq!(
+                events.get_balance(&signer.address),
+                BigInt::from(token_client.balance(&signer.address)),
+                "balance differs from the balance implied by the token's events",
+            );
+        }
+    }
 }
 
 /// Advance time, but do it in increments, periodically pinging the contract to
@@ -645,6 +1850,7 @@ fn advance_time(
     token_contract_id_bytes: &[u8],
     ledgers: u32,
     env_prng_seed: &mut u64,
+    upgraded: bool,
 ) -> Env {
     let to_ledger = env
         .ledger()
@@ -667,7 +1873,7 @@ fn advance_time(
 
         let token_contract_id =
             Address::from_string_bytes(&Bytes::from_slice(&env, &token_contract_id_bytes));
-        config.reregister_contract(&env, &token_contract_id);
+        config.reregister_contract(&env, &token_contract_id, upgraded);
 
         if next_ledger == to_ledger {
             break;
@@ -752,20 +1958,148 @@ fn purge_expired_entries(snapshot: &mut Snapshot) {
     });
 }
 
-fn verify_token_contract_result(env: &Env, r: &TokenContractResult) {
+fn verify_token_contract_result(env: &Env, r: &UnitResult) {
     match r {
-        Err(Ok(e)) => {
-            if e.is_type(ScErrorType::WasmVm) && e.is_code(ScErrorCode::InvalidAction) {
-                let msg = "contract failed with InvalidAction - unexpected panic?";
-                eprintln!("{msg}");
-                print_diagnostics(env);
-                panic!("{msg}");
-            }
+        // A panicking native contract fails as if it hit a WASM `unreachable`.
+        // WASM contracts can trap in other ways too,
+        // e.g. by dividing by zero or accessing memory out of bounds,
+        // and none of them are a proper error.
+        Err(Ok(e)) if e.is_type(ScErrorType::WasmVm) => {
+            let msg = if e.is_code(ScErrorCode::InvalidAction) {
+                "contract failed with InvalidAction - unexpected panic?".to_string()
+            } else {
+                format!("contract trapped with {e:?}")
+            };
+            eprintln!("{msg}");
+            print_diagnostics(env);
+            panic!("{msg}");
         }
         _ => {}
     }
 }
 
+/// Check a call's result against what the model predicted.
+fn verify_prediction(env: &Env, fn_name: &str, expect: Expect, r: &UnitResult) {
+    let msg = match (expect, r) {
+        (Expect::Success, Err(e)) => {
+            format!("{fn_name} failed with {e:?}, but the model says it must succeed")
+        }
+        (Expect::Failure, Ok(_)) => {
+            format!("{fn_name} succeeded, but the model says it must fail")
+        }
+        _ => return,
+    };
+
+    eprintln!("{msg}");
+    print_diagnostics(env);
+    panic!("{msg}");
+}
+
+/// Events emitted by the token contract, as `(topics, data)`.
+///
+/// Events from failed calls are still recorded by the host,
+/// but are excluded here.
+fn token_events(env: &Env, token_contract_id: &Address) -> RustVec<(VecM<ScVal>, ScVal)> {
+    let token_contract_id = ScAddress::try_from(token_contract_id).unwrap();
+
+    env.host()
+        .get_events()
+        .unwrap()
+        .0
+        .into_iter()
+        .filter(|event| !event.failed_call)
+        .filter_map(|event| match event.event {
+            ContractEvent {
+                type_: ContractEventType::Contract,
+                contract_id: Some(contract_id),
+                body: ContractEventBody::V0(ContractEventV0 { topics, data }),
+                ..
+            } if ScAddress::Contract(contract_id.clone()) == token_contract_id => {
+                Some((topics, data))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Check that a successful call emitted exactly the expected events
+/// from the token contract.
+///
+/// `events_before` is the number of token events before the call.
+fn verify_events(
+    env: &Env,
+    fn_name: &str,
+    token_contract_id: &Address,
+    events_before: usize,
+    expected: &[(soroban_sdk::Vec<Val>, Val)],
+) {
+    let actual: RustVec<_> = token_events(env, token_contract_id)
+        .into_iter()
+        .skip(events_before)
+        .collect();
+    let expected: RustVec<_> = expected
+        .iter()
+        .map(|(topics, data)| {
+            (
+                VecM::try_from(topics.clone()).unwrap(),
+                ScVal::try_from_val(env, data).unwrap(),
+            )
+        })
+        .collect();
+
+    if actual != expected {
+        let msg = format!("{fn_name} did not emit the expected events");
+        eprintln!("{msg}");
+        eprintln!("expected: {expected:#?}");
+        eprintln!("actual: {actual:#?}");
+        print_diagnostics(env);
+        panic!("{msg}");
+    }
+}
+
+/// Check that a failed call left the token's storage,
+/// every account and trustline, and their TTLs,
+/// exactly as they were before the call.
+///
+/// Events from a failed call are marked as such by the host,
+/// and never seen by `token_events`, so they aren't checked.
+fn verify_failure_changed_nothing(
+    env: &Env,
+    fn_name: &str,
+    token_contract_id: &Address,
+    r: &UnitResult,
+    pre_snapshot: &Snapshot,
+) {
+    if r.is_ok() {
+        return;
+    }
+
+    // Only the token's own storage and the holders' accounts are checked.
+    // Token-specific admin clients may write elsewhere outside the call,
+    // e.g. Comet funds the pool's underlying tokens before minting.
+    let token_sc_address = ScAddress::try_from(token_contract_id).unwrap();
+    let post_snapshot = env.to_snapshot();
+    let changes: RustVec<_> = diff_ledger(pre_snapshot, &post_snapshot)
+        .into_iter()
+        .filter(|change| change.is_contract_data_of(&token_sc_address) || change.is_account_entry())
+        .collect();
+
+    if !changes.is_empty() {
+        let msg = format!("{fn_name} failed, but changed the ledger");
+        eprintln!("{msg}");
+        for change in &changes {
+            eprintln!("{:?}: {:?}", change.kind, change.key);
+        }
+        print_diagnostics(env);
+        panic!("{msg}");
+    }
+}
+
+fn max_live_until_ledger(env: &Env) -> u32 {
+    let ledger = env.ledger().get();
+    ledger.sequence_number + ledger.max_entry_ttl - 1
+}
+
 fn print_diagnostics(env: &Env) {
     eprintln!("recent events (10):");
     for (i, event) in env.events().all().iter().rev().take(10).enumerate() {
@@ -773,88 +2107,254 @@ fn print_diagnostics(env: &Env) {
     }
 }
 
-#[contract]
-pub struct MockAuthContract;
+/// Deliberate flaws in the auths provided by [`mock_auths_for_command`]
+/// that must cause the host to reject them.
+#[derive(Copy, Clone, Debug, Default)]
+struct AuthTampering {
+    corruption: Option<AuthCorruption>,
+    mismatch: Option<InvocationMismatch>,
+}
+
+/// The auths provided by [`mock_auths_for_command`].
+struct ProvidedAuths {
+    /// Whether each account provided a valid auth entry that it will authorize.
+    valid: RustVec<bool>,
+    /// Whether each account provided a deliberately tampered auth entry.
+    tampered: RustVec<bool>,
+    tampering: AuthTampering,
+    /// The function and arguments of the call being authorized.
+    fn_name: Symbol,
+    args: soroban_sdk::Vec<Val>,
+    /// The call and auth entries, in a form that survives recreating the `Env`.
+    invocation: InvokeContractArgs,
+    auth_entries: RustVec<SorobanAuthorizationEntry>,
+}
 
-#[contractimpl]
-impl MockAuthContract {
-    #[allow(non_snake_case)]
-    pub fn __check_auth(_signature_payload: Val, _signatures: Val, _auth_context: Val) {}
+/// A successful call, and the auth entries it was made with,
+/// kept so they can be replayed.
+struct AcceptedCall {
+    invocation: InvokeContractArgs,
+    auth_entries: RustVec<SorobanAuthorizationEntry>,
+}
+
+impl ProvidedAuths {
+    fn is_valid(&self, account_index: usize) -> bool {
+        self.valid[account_index]
+    }
+
+    /// Keep the auth entries of a successful call for [`Command::Replay`].
+    ///
+    /// Only the entries the call actually consumed are kept.
+    /// The others were never used and would be accepted
+    /// if the addresses that must authorize the call change,
+    /// e.g. after the admin is changed.
+    fn record_accepted_call(
+        &self,
+        env: &Env,
+        r: &UnitResult,
+        accepted_calls: &mut RustVec<AcceptedCall>,
+    ) {
+        if r.is_ok() {
+            let authorizers: RustVec<ScAddress> = env
+                .auths()
+                .iter()
+                .map(|(address, _)| ScAddress::try_from(address).unwrap())
+                .collect();
+            let auth_entries = self
+                .auth_entries
+                .iter()
+                .filter(|entry| match &entry.credentials {
+                    SorobanCredentials::Address(credentials) => {
+                        authorizers.contains(&credentials.address)
+                    }
+                    SorobanCredentials::SourceAccount => false,
+                })
+                .cloned()
+                .collect();
+            accepted_calls.push(AcceptedCall {
+                invocation: self.invocation.clone(),
+                auth_entries,
+            });
+        }
+    }
+
+    /// Check that the call failed if the account that needed to authorize it
+    /// provided a tampered auth entry.
+    fn verify_tampering_rejected(
+        &self,
+        env: &Env,
+        fn_name: &str,
+        current_state: &CurrentState,
+        account_index: usize,
+        r: &UnitResult,
+    ) {
+        if self.tampered[account_index] && r.is_ok() {
+            let address = &current_state.accounts[account_index].address;
+            let msg = format!(
+                "{fn_name} succeeded with a tampered auth entry for {address:?}: {:?}",
+                self.tampering,
+            );
+            eprintln!("{msg}");
+            print_diagnostics(env);
+            panic!("{msg}");
+        }
+    }
+
+    /// Check that a successful call required auth from exactly
+    /// the account at `account_index`, for exactly the call's arguments,
+    /// per SEP-41.
+    fn verify_required_auths(
+        &self,
+        env: &Env,
+        current_state: &CurrentState,
+        account_index: usize,
+        r: &UnitResult,
+    ) {
+        if r.is_err() {
+            return;
+        }
+
+        let expected = vec![(
+            current_state.accounts[account_index].address.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    current_state.token_client.address.clone(),
+                    self.fn_name.clone(),
+                    self.args.clone(),
+                )),
+                sub_invocations: vec![],
+            },
+        )];
+        let actual = env.auths();
+
+        if actual != expected {
+            let msg = format!("{} required unexpected auths", self.fn_name.to_string());
+            eprintln!("{msg}");
+            eprintln!("expected: {expected:#?}");
+            eprintln!("actual: {actual:#?}");
+            panic!("{msg}");
+        }
+    }
 }
 
 fn mock_auths_for_command(
     env: &Env,
     fn_name: &str,
     auths: &[bool],
+    tampering: AuthTampering,
     current_state: &CurrentState,
-    token_contract_id_bytes: &[u8],
     signature_nonce: &mut i64,
     args: soroban_sdk::Vec<Val>,
-) {
-    let curr_ledger = env.ledger().sequence();
-    let max_entry_ttl = env.ledger().get().max_entry_ttl;
-    let expiration_ledger = curr_ledger + max_entry_ttl - 1;
-
-    let token_contract_id =
-        Address::from_string_bytes(&Bytes::from_slice(env, token_contract_id_bytes));
-    let token_contract_sc_address = ScAddress::try_from(token_contract_id).unwrap();
+) -> ProvidedAuths {
+    let max_expiration_ledger = max_live_until_ledger(env);
+    let network_id: [u8; 32] = env
+        .host()
+        .with_ledger_info(|li: &LedgerInfo| Ok(li.network_id))
+        .unwrap();
+
+    let token_contract_sc_address =
+        ScAddress::try_from(&current_state.token_client.address).unwrap();
+
+    // The invocation the auth entries authorize,
+    // which only matches the actual call if there is no mismatch.
+    let actual_invocation = InvokeContractArgs {
+        contract_address: token_contract_sc_address,
+        function_name: ScSymbol(fn_name.try_into().unwrap()),
+        args: VecM::try_from(args.clone()).unwrap(),
+    };
+    let mut invocation = actual_invocation.clone();
+    if let Some(mismatch) = tampering.mismatch {
+        mismatch_invocation(&mut invocation, mismatch, current_state);
+    }
 
+    let number_of_accounts = current_state.accounts.len();
+    let mut provided = ProvidedAuths {
+        valid: vec![false; number_of_accounts],
+        tampered: vec![false; number_of_accounts],
+        tampering,
+        fn_name: Symbol::new(env, fn_name),
+        args,
+        invocation: actual_invocation,
+        auth_entries: RustVec::new(),
+    };
     let mut auth_entries = RustVec::new();
 
-    for i in 0..NUMBER_OF_ADDRESSES {
+    for i in 0..number_of_accounts {
         if auths[i] {
             let signer = &current_state.accounts[i];
             let sc_address = ScAddress::try_from(signer.address.clone()).unwrap();
 
-            let is_contract_address = signer.key.is_none();
+            signer.register_account_contract(env);
 
-            // contract addresses need to have registered contracts to be authorizers,
-            // at least according to the sdk's mock_auths method
-            if is_contract_address {
-                env.register_contract(&signer.address, MockAuthContract);
-            }
+            let mut nonce = *signature_nonce;
+            let mut expiration_ledger = max_expiration_ledger;
+            let mut network_id = network_id;
 
-            let mut credentials = SorobanAddressCredentials {
-                address: sc_address,
-                nonce: *signature_nonce,
-                signature_expiration_ledger: expiration_ledger,
-                signature: ScVal::Void, // updated below for non-contract addresses
+            // Some corruptions aren't possible in every state,
+            // in which case the credentials are left valid.
+            let is_corrupted = match tampering.corruption {
+                None => false,
+                Some(AuthCorruption::ReusedNonce) => match find_used_nonce(env, &sc_address) {
+                    Some(used_nonce) => {
+                        nonce = used_nonce;
+                        true
+                    }
+                    None => false,
+                },
+                Some(AuthCorruption::ExpiredSignature) => {
+                    match env.ledger().sequence().checked_sub(1) {
+                        Some(past_ledger) => {
+                            expiration_ledger = past_ledger;
+                            true
+                        }
+                        None => false,
+                    }
+                }
+                Some(AuthCorruption::WrongNetworkId) => {
+                    network_id[0] ^= 0xff;
+                    true
+                }
+                Some(AuthCorruption::WrongSigner) | Some(AuthCorruption::TruncatedSignature) => {
+                    true
+                }
             };
 
             let root_invocation = SorobanAuthorizedInvocation {
-                function: SorobanAuthorizedFunction::ContractFn(InvokeContractArgs {
-                    contract_address: token_contract_sc_address.clone(),
-                    function_name: ScSymbol(fn_name.try_into().unwrap()),
-                    args: VecM::try_from(args.clone()).unwrap(),
-                }),
+                function: SorobanAuthorizedFunction::ContractFn(invocation.clone()),
                 sub_invocations: Default::default(),
             };
 
-            if let Some(key) = &signer.key {
-                let signature_payload_preimage =
-                    HashIdPreimage::SorobanAuthorization(HashIdPreimageSorobanAuthorization {
-                        network_id: env
-                            .host()
-                            .with_ledger_info(|li: &LedgerInfo| Ok(li.network_id))
-                            .unwrap()
-                            .try_into()
-                            .unwrap(),
-                        invocation: root_invocation.clone(),
-                        nonce: *signature_nonce,
-                        signature_expiration_ledger: expiration_ledger,
-                    });
-
-                let mut buf = vec![];
-                let mut unlimited_buf = Limited::new(&mut buf, Limits::none());
-                signature_payload_preimage
-                    .write_xdr(&mut unlimited_buf)
-                    .unwrap();
-                let signature_payload: [u8; 32] = Sha256::digest(&buf).try_into().unwrap();
-
-                let signature = sign_payload_for_account(env, key, &signature_payload);
-                let signatures = soroban_sdk::vec![env, signature];
-                credentials.signature = signatures.try_into().unwrap();
-            }
+            let signature_payload_preimage =
+                HashIdPreimage::SorobanAuthorization(HashIdPreimageSorobanAuthorization {
+                    network_id: network_id.into(),
+                    invocation: root_invocation.clone(),
+                    nonce,
+                    signature_expiration_ledger: expiration_ledger,
+                });
+
+            let mut buf = vec![];
+            let mut unlimited_buf = Limited::new(&mut buf, Limits::none());
+            signature_payload_preimage
+                .write_xdr(&mut unlimited_buf)
+                .unwrap();
+            let signature_payload: [u8; 32] = Sha256::digest(&buf).try_into().unwrap();
+
+            let signature = match tampering.corruption {
+                Some(AuthCorruption::WrongSigner) => {
+                    signer.sign_with_wrong_keys(env, &signature_payload)
+                }
+                Some(AuthCorruption::TruncatedSignature) => {
+                    truncate_signatures(signer.sign(env, &signature_payload))
+                }
+                _ => signer.sign(env, &signature_payload),
+            };
+
+            let credentials = SorobanAddressCredentials {
+                address: sc_address,
+                nonce,
+                signature_expiration_ledger: expiration_ledger,
+                signature,
+            };
 
             *signature_nonce += 1;
 
@@ -863,75 +2363,190 @@ fn mock_auths_for_command(
                 root_invocation,
             };
             auth_entries.push(auth_entry);
+
+            let is_tampered = is_corrupted || tampering.mismatch.is_some();
+            provided.tampered[i] = is_tampered;
+            provided.valid[i] = signer.can_authorize() && !is_tampered;
         }
     }
 
     env.set_auths(&auth_entries);
+    provided.auth_entries = auth_entries;
+
+    provided
+}
+
+/// Change an invocation so that it no longer matches the call being made.
+fn mismatch_invocation(
+    invocation: &mut InvokeContractArgs,
+    mismatch: InvocationMismatch,
+    current_state: &CurrentState,
+) {
+    match mismatch {
+        InvocationMismatch::WrongFunction => {
+            let fn_names = ["approve", "transfer", "transfer_from", "burn", "burn_from"];
+            let fn_name = invocation.function_name.to_utf8_string().unwrap();
+            let i = fn_names.iter().position(|f| *f == fn_name).unwrap_or(0);
+            let wrong_fn_name = fn_names[(i + 1) % fn_names.len()];
+            invocation.function_name = ScSymbol(wrong_fn_name.try_into().unwrap());
+        }
+        InvocationMismatch::WrongAmount => {
+            let mut args = invocation.args.to_vec();
+            let amount = args
+                .iter_mut()
+                .find(|arg| matches!(arg, ScVal::I128(_)))
+                .expect("amount");
+            let ScVal::I128(parts) = amount else {
+                unreachable!()
+            };
+            let value = ((parts.hi as i128) << 64) | parts.lo as i128;
+            let wrong_value = value.wrapping_add(1);
+            *amount = ScVal::I128(Int128Parts {
+                hi: (wrong_value >> 64) as i64,
+                lo: wrong_value as u64,
+            });
+            invocation.args = args.try_into().unwrap();
+        }
+        InvocationMismatch::WrongAddress => {
+            let mut args = invocation.args.to_vec();
+            let address = args
+                .iter_mut()
+                .rev()
+                .find(|arg| matches!(arg, ScVal::Address(_)))
+                .expect("address");
+            let wrong_address = current_state
+                .accounts
+                .iter()
+                .map(|account| ScVal::Address(ScAddress::try_from(&account.address).unwrap()))
+                .find(|account| account != address)
+                .expect("another account");
+            *address = wrong_address;
+            invocation.args = args.try_into().unwrap();
+        }
+        InvocationMismatch::WrongContract => {
+            invocation.contract_address = ScAddress::Contract(Hash([0xff; 32]));
+        }
+    }
 }
 
-#[contracttype]
-pub(crate) struct AccountEd25519Signature {
-    pub(crate) public_key: BytesN<32>,
-    pub(crate) signature: BytesN<64>,
+/// Find a nonce that `address` has already consumed and is still live.
+fn find_used_nonce(env: &Env, address: &ScAddress) -> Option<i64> {
+    let ledger = env.ledger().sequence();
+    env.to_snapshot()
+        .ledger
+        .ledger_entries
+        .iter()
+        .find_map(|(key, (_, live_until_ledger))| match &**key {
+            LedgerKey::ContractData(LedgerKeyContractData {
+                contract,
+                key: ScVal::LedgerKeyNonce(ScNonceKey { nonce }),
+                ..
+            }) if contract == address && live_until_ledger.unwrap_or(0) >= ledger => Some(*nonce),
+            _ => None,
+        })
 }
 
-fn sign_payload_for_account(
-    env: &Env,
-    signer: &SigningKey,
-    payload: &[u8],
-) -> AccountEd25519Signature {
-    AccountEd25519Signature {
-        public_key: BytesN::<32>::try_from_val(env, &signer.verifying_key().to_bytes()).unwrap(),
-        signature: BytesN::<64>::try_from_val(env, &signer.sign(payload).to_bytes()).unwrap(),
+/// Truncate every ed25519 signature in a signature value to half its length.
+fn truncate_signatures(signature: ScVal) -> ScVal {
+    match signature {
+        ScVal::Bytes(bytes) if bytes.len() == 64 => {
+            ScVal::Bytes(bytes[..32].to_vec().try_into().unwrap())
+        }
+        ScVal::Vec(Some(vals)) => ScVal::Vec(Some(
+            vals.iter()
+                .cloned()
+                .map(truncate_signatures)
+                .collect::<RustVec<_>>()
+                .try_into()
+                .unwrap(),
+        )),
+        ScVal::Map(Some(entries)) => ScVal::Map(Some(
+            entries
+                .iter()
+                .cloned()
+                .map(|ScMapEntry { key, val }| ScMapEntry {
+                    key,
+                    val: truncate_signatures(val),
+                })
+                .collect::<RustVec<_>>()
+                .try_into()
+                .unwrap(),
+        )),
+        other => other,
+    }
+}
+
+/// Check that an accessor call did not change the token's storage.
+///
+/// Extending TTLs is allowed if the config allows it.
+fn check_accessor_is_read_only(
+    fn_name: &str,
+    current_state: &CurrentState,
+    pre_snapshot: &Snapshot,
+    post_snapshot: &Snapshot,
+) {
+    let contract_address = ScAddress::try_from(&current_state.token_client.address).unwrap();
+
+    let changes: RustVec<_> = diff_ledger(pre_snapshot, post_snapshot)
+        .into_iter()
+        .filter(|change| change.is_contract_data_of(&contract_address))
+        .filter(|change| {
+            let is_ttl_change = matches!(change.kind, ChangeKind::Ttl { .. });
+            !(is_ttl_change && current_state.accessors_may_extend_ttl)
+        })
+        .collect();
+
+    if !changes.is_empty() {
+        let msg = format!("{fn_name} changed contract storage");
+        eprintln!("{msg}");
+        for change in &changes {
+            eprintln!("{change:?}");
+        }
+        panic!("{msg}");
     }
 }
 
-/// Check that after transfer_from,
-/// if the transfer amount is 0,
-/// that the ttl of the allowance has not changed.
+/// Check that a successful call that should have had no effect,
+/// like a zero-amount transfer, did not write to the token's storage.
 ///
-/// Because we can't know the storage key of the allowance,
-/// we use a heuristic - that _no_ temporary ttls have changed.
+/// Changing, deleting, or creating temporary entries,
+/// or changing the TTL of temporary entries (i.e. allowances), are errors.
+/// Creating persistent entries and extending persistent and instance TTLs
+/// are allowed: both the Stellar Asset Contract and the example token
+/// write zero balances, and extend TTLs whenever they are used.
 //
 // cc https://github.com/brson/soroban-token-fuzzer/issues/1
-fn check_for_zero_allowance_bug(
+fn check_noop_did_not_write(
+    env: &Env,
+    fn_name: &str,
     contract_address: &Address,
-    amount: i128,
-    pre_snapshot: Snapshot,
-    post_snapshot: Snapshot,
+    pre_snapshot: &Snapshot,
 ) {
     let contract_address = ScAddress::try_from(contract_address).unwrap();
-
-    if amount != 0 {
-        return;
+    let post_snapshot = env.to_snapshot();
+
+    let writes: RustVec<_> = diff_ledger(pre_snapshot, &post_snapshot)
+        .into_iter()
+        .filter(|change| change.is_contract_data_of(&contract_address))
+        .filter(|change| {
+            let is_temporary = change.storage_type() == Some(StorageType::Temporary);
+            match change.kind {
+                ChangeKind::Modified | ChangeKind::Deleted => true,
+                ChangeKind::Created | ChangeKind::Ttl { .. } => is_temporary,
+            }
+        })
+        .collect();
+
+    if !writes.is_empty() {
+        let msg = format!("{fn_name} should have had no effect, but wrote to storage");
+        eprintln!("{msg}");
+        for change in &writes {
+            let storage_type = change.storage_type().expect("contract data");
+            eprintln!("{storage_type:?} {:?}: {:?}", change.kind, change.key);
+        }
+        print_diagnostics(env);
+        panic!("{msg}");
     }
-
-    let get_temp_ttls = |snapshot: &Snapshot| -> RustVec<Option<u32>> {
-        snapshot
-            .ledger
-            .ledger_entries
-            .iter()
-            .filter_map(|(key, (_entry, ttl))| {
-                match **key {
-                    LedgerKey::ContractData(ref data) => {
-                        // Only worry about storage for the contract under test
-                        if data.contract != contract_address {
-                            return None;
-                        }
-                        match data.durability {
-                            ContractDataDurability::Temporary => Some(*ttl),
-                            _ => None,
-                        }
-                    }
-                    _ => None,
-                }
-            })
-            .collect()
-    };
-
-    let pre_ttls = get_temp_ttls(&pre_snapshot);
-    let post_ttls = get_temp_ttls(&post_snapshot);
-    assert_eq!(pre_ttls, post_ttls);
 }
 
 fn set_env_prng_seed(env: &Env, env_prng_seed: &mut u64) {
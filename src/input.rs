@@ -1,11 +1,17 @@
-use crate::addrgen::AddressGenerator;
+use crate::addrgen::{AddressGenerator, ClassicAsset};
 use crate::util::SmartI128;
 use crate::DAY_IN_LEDGERS;
 use arbitrary::Unstructured;
 use soroban_sdk::testutils::arbitrary::arbitrary;
 use std::vec::Vec as RustVec;
 
-pub const NUMBER_OF_ADDRESSES: usize = 3;
+/// The fewest addresses the fuzzer will generate for a run.
+pub const MIN_NUMBER_OF_ADDRESSES: usize = 2;
+/// The most addresses the fuzzer will generate for a run.
+///
+/// Account indexes and auths in commands are generated for this many addresses,
+/// then wrapped to the number of addresses actually used by the run.
+pub const MAX_NUMBER_OF_ADDRESSES: usize = 10;
 
 /// Input generated by the fuzzer as the argument to `fuzz_target!`.
 ///
@@ -13,9 +19,71 @@ pub const NUMBER_OF_ADDRESSES: usize = 3;
 #[derive(Clone, Debug, arbitrary::Arbitrary)]
 pub struct Input {
     pub address_generator: AddressGenerator,
+    /// The classic asset wrapped by the native token.
+    pub asset: ClassicAsset,
+    pub init: InitInput,
     pub transactions: RustVec<Transaction>,
 }
 
+/// The token's one-time initialization.
+#[derive(Clone, Debug, arbitrary::Arbitrary)]
+pub struct InitInput {
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
+    pub admin_account_index: usize,
+    pub params: InitParams,
+}
+
+/// The parameters passed to the token's initialization function.
+///
+/// Tokens may reject these, or ignore them,
+/// as the Stellar Asset Contract does.
+#[derive(Clone, Debug, arbitrary::Arbitrary)]
+pub struct InitParams {
+    #[arbitrary(with = arbitrary_decimals)]
+    pub decimals: u32,
+    #[arbitrary(with = arbitrary_metadata)]
+    pub name: std::string::String,
+    #[arbitrary(with = arbitrary_metadata)]
+    pub symbol: std::string::String,
+}
+
+impl Default for InitParams {
+    fn default() -> InitParams {
+        InitParams {
+            decimals: 10,
+            name: "token".to_string(),
+            symbol: "TKN".to_string(),
+        }
+    }
+}
+
+fn arbitrary_decimals(u: &mut Unstructured) -> arbitrary::Result<u32> {
+    // mostly plausible, sometimes more than fits in a u8
+    if u.ratio(3, 4)? {
+        u.int_in_range(0..=18)
+    } else if u.ratio(1, 2)? {
+        u.int_in_range(u8::MAX as u32..=u8::MAX as u32 + 1)
+    } else {
+        u.arbitrary()
+    }
+}
+
+fn arbitrary_metadata(u: &mut Unstructured) -> arbitrary::Result<std::string::String> {
+    const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+    match u.int_in_range(0..=3)? {
+        0 => Ok(std::string::String::new()),
+        1 => Ok("x".repeat(u.int_in_range(256..=4096)?)),
+        // anything, often not ASCII
+        2 => u.arbitrary(),
+        _ => {
+            let len = u.int_in_range(1..=12)?;
+            (0..len)
+                .map(|_| u.choose(CHARS).map(|c| *c as char))
+                .collect()
+        }
+    }
+}
+
 #[derive(Clone, Debug, arbitrary::Arbitrary)]
 pub struct Transaction {
     pub commands: RustVec<Command>,
@@ -31,159 +99,351 @@ pub enum Command {
     Transfer(TransferInput),
     BurnFrom(BurnFromInput),
     Burn(BurnInput),
+    Balance(BalanceInput),
+    Allowance(AllowanceInput),
+    Decimals,
+    Name,
+    Symbol,
+    Replay(ReplayInput),
+    SetAdmin(SetAdminInput),
+    Clawback(ClawbackInput),
+    SetAuthorized(SetAuthorizedInput),
+    Authorized(AuthorizedInput),
+    Reinitialize(ReinitializeInput),
+    /// Switch the token to its upgraded implementation,
+    /// starting with the next transaction.
+    ///
+    /// This does nothing unless the token was given an upgrade
+    /// with [`Config::with_upgrade`](crate::Config::with_upgrade).
+    Upgrade,
     // These two exist just to make it more likely the fuzzer
     // will generate a successful transfer_from / burn_from call
     ApproveAndTransferFrom(ApproveAndTransferFromInput),
     ApproveAndBurnFrom(ApproveAndBurnFromInput),
 }
 
+/// A way to corrupt the credentials of an auth entry,
+/// such that the host must reject it.
+#[derive(Copy, Clone, Debug, arbitrary::Arbitrary)]
+pub enum AuthCorruption {
+    /// Use a nonce the signer has already used.
+    ReusedNonce,
+    /// Set the signature expiration ledger in the past.
+    ExpiredSignature,
+    /// Sign with keys that don't belong to the signer.
+    WrongSigner,
+    /// Sign for a different network.
+    WrongNetworkId,
+    /// Truncate every ed25519 signature.
+    TruncatedSignature,
+}
+
+/// A way to make a correctly signed auth entry
+/// authorize an invocation other than the one actually made.
+#[derive(Copy, Clone, Debug, arbitrary::Arbitrary)]
+pub enum InvocationMismatch {
+    /// Authorize a different token function.
+    WrongFunction,
+    /// Authorize a different amount.
+    WrongAmount,
+    /// Authorize a different recipient, spender, or owner,
+    /// i.e. replace the last address argument.
+    WrongAddress,
+    /// Authorize a call to a different contract.
+    WrongContract,
+}
+
 #[derive(Clone, Debug, arbitrary::Arbitrary)]
 pub struct MintInput {
     pub amount: SmartI128,
-    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=NUMBER_OF_ADDRESSES - 1))]
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
     pub to_account_index: usize,
     #[arbitrary(with = |u: &mut Unstructured| {
         // biased bool - only sometimes decline the auth
-        Ok(<[bool; NUMBER_OF_ADDRESSES]>::try_from(
+        Ok(<[bool; MAX_NUMBER_OF_ADDRESSES]>::try_from(
             std::iter::from_fn(|| Some(u.ratio(9, 10).unwrap_or(true)))
-                .take(NUMBER_OF_ADDRESSES)
+                .take(MAX_NUMBER_OF_ADDRESSES)
                 .collect::<Vec<_>>()
         ).unwrap())
     })]
-    pub auths: [bool; NUMBER_OF_ADDRESSES],
+    pub auths: [bool; MAX_NUMBER_OF_ADDRESSES],
+    #[arbitrary(with = |u: &mut Unstructured| {
+        // only sometimes corrupt the auths
+        if u.ratio(1, 10)? { Ok(Some(u.arbitrary()?)) } else { Ok(None) }
+    })]
+    pub auth_corruption: Option<AuthCorruption>,
+    #[arbitrary(with = |u: &mut Unstructured| {
+        // only sometimes sign the wrong invocation
+        if u.ratio(1, 10)? { Ok(Some(u.arbitrary()?)) } else { Ok(None) }
+    })]
+    pub invocation_mismatch: Option<InvocationMismatch>,
 }
 
 #[derive(Clone, Debug, arbitrary::Arbitrary)]
 pub struct ApproveInput {
     pub amount: SmartI128,
     pub expiration_ledger: u32,
-    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=NUMBER_OF_ADDRESSES - 1))]
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
     pub from_account_index: usize,
-    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=NUMBER_OF_ADDRESSES - 1))]
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
     pub spender_account_index: usize,
     #[arbitrary(with = |u: &mut Unstructured| {
         // biased bool - only sometimes decline the auth
-        Ok(<[bool; NUMBER_OF_ADDRESSES]>::try_from(
+        Ok(<[bool; MAX_NUMBER_OF_ADDRESSES]>::try_from(
             std::iter::from_fn(|| Some(u.ratio(9, 10).unwrap_or(true)))
-                .take(NUMBER_OF_ADDRESSES)
+                .take(MAX_NUMBER_OF_ADDRESSES)
                 .collect::<Vec<_>>()
         ).unwrap())
     })]
-    pub auths: [bool; NUMBER_OF_ADDRESSES],
+    pub auths: [bool; MAX_NUMBER_OF_ADDRESSES],
+    #[arbitrary(with = |u: &mut Unstructured| {
+        // only sometimes corrupt the auths
+        if u.ratio(1, 10)? { Ok(Some(u.arbitrary()?)) } else { Ok(None) }
+    })]
+    pub auth_corruption: Option<AuthCorruption>,
+    #[arbitrary(with = |u: &mut Unstructured| {
+        // only sometimes sign the wrong invocation
+        if u.ratio(1, 10)? { Ok(Some(u.arbitrary()?)) } else { Ok(None) }
+    })]
+    pub invocation_mismatch: Option<InvocationMismatch>,
 }
 
 #[derive(Clone, Debug, arbitrary::Arbitrary)]
 pub struct TransferFromInput {
     pub amount: SmartI128,
-    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=NUMBER_OF_ADDRESSES - 1))]
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
     pub spender_account_index: usize,
-    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=NUMBER_OF_ADDRESSES - 1))]
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
     pub from_account_index: usize,
-    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=NUMBER_OF_ADDRESSES - 1))]
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
     pub to_account_index: usize,
     #[arbitrary(with = |u: &mut Unstructured| {
         // biased bool - only sometimes decline the auth
-        Ok(<[bool; NUMBER_OF_ADDRESSES]>::try_from(
+        Ok(<[bool; MAX_NUMBER_OF_ADDRESSES]>::try_from(
             std::iter::from_fn(|| Some(u.ratio(9, 10).unwrap_or(true)))
-                .take(NUMBER_OF_ADDRESSES)
+                .take(MAX_NUMBER_OF_ADDRESSES)
                 .collect::<Vec<_>>()
         ).unwrap())
     })]
-    pub auths: [bool; NUMBER_OF_ADDRESSES],
+    pub auths: [bool; MAX_NUMBER_OF_ADDRESSES],
+    #[arbitrary(with = |u: &mut Unstructured| {
+        // only sometimes corrupt the auths
+        if u.ratio(1, 10)? { Ok(Some(u.arbitrary()?)) } else { Ok(None) }
+    })]
+    pub auth_corruption: Option<AuthCorruption>,
+    #[arbitrary(with = |u: &mut Unstructured| {
+        // only sometimes sign the wrong invocation
+        if u.ratio(1, 10)? { Ok(Some(u.arbitrary()?)) } else { Ok(None) }
+    })]
+    pub invocation_mismatch: Option<InvocationMismatch>,
 }
 
 #[derive(Clone, Debug, arbitrary::Arbitrary)]
 pub struct TransferInput {
     pub amount: SmartI128,
-    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=NUMBER_OF_ADDRESSES - 1))]
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
     pub from_account_index: usize,
-    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=NUMBER_OF_ADDRESSES - 1))]
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
     pub to_account_index: usize,
     #[arbitrary(with = |u: &mut Unstructured| {
         // biased bool - only sometimes decline the auth
-        Ok(<[bool; NUMBER_OF_ADDRESSES]>::try_from(
+        Ok(<[bool; MAX_NUMBER_OF_ADDRESSES]>::try_from(
             std::iter::from_fn(|| Some(u.ratio(9, 10).unwrap_or(true)))
-                .take(NUMBER_OF_ADDRESSES)
+                .take(MAX_NUMBER_OF_ADDRESSES)
                 .collect::<Vec<_>>()
         ).unwrap())
     })]
-    pub auths: [bool; NUMBER_OF_ADDRESSES],
+    pub auths: [bool; MAX_NUMBER_OF_ADDRESSES],
+    #[arbitrary(with = |u: &mut Unstructured| {
+        // only sometimes corrupt the auths
+        if u.ratio(1, 10)? { Ok(Some(u.arbitrary()?)) } else { Ok(None) }
+    })]
+    pub auth_corruption: Option<AuthCorruption>,
+    #[arbitrary(with = |u: &mut Unstructured| {
+        // only sometimes sign the wrong invocation
+        if u.ratio(1, 10)? { Ok(Some(u.arbitrary()?)) } else { Ok(None) }
+    })]
+    pub invocation_mismatch: Option<InvocationMismatch>,
 }
 
 #[derive(Clone, Debug, arbitrary::Arbitrary)]
 pub struct BurnFromInput {
     pub amount: SmartI128,
-    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=NUMBER_OF_ADDRESSES - 1))]
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
     pub spender_account_index: usize,
-    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=NUMBER_OF_ADDRESSES - 1))]
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
     pub from_account_index: usize,
     #[arbitrary(with = |u: &mut Unstructured| {
         // biased bool - only sometimes decline the auth
-        Ok(<[bool; NUMBER_OF_ADDRESSES]>::try_from(
+        Ok(<[bool; MAX_NUMBER_OF_ADDRESSES]>::try_from(
             std::iter::from_fn(|| Some(u.ratio(9, 10).unwrap_or(true)))
-                .take(NUMBER_OF_ADDRESSES)
+                .take(MAX_NUMBER_OF_ADDRESSES)
                 .collect::<Vec<_>>()
         ).unwrap())
     })]
-    pub auths: [bool; NUMBER_OF_ADDRESSES],
+    pub auths: [bool; MAX_NUMBER_OF_ADDRESSES],
+    #[arbitrary(with = |u: &mut Unstructured| {
+        // only sometimes corrupt the auths
+        if u.ratio(1, 10)? { Ok(Some(u.arbitrary()?)) } else { Ok(None) }
+    })]
+    pub auth_corruption: Option<AuthCorruption>,
+    #[arbitrary(with = |u: &mut Unstructured| {
+        // only sometimes sign the wrong invocation
+        if u.ratio(1, 10)? { Ok(Some(u.arbitrary()?)) } else { Ok(None) }
+    })]
+    pub invocation_mismatch: Option<InvocationMismatch>,
 }
 
 #[derive(Clone, Debug, arbitrary::Arbitrary)]
 pub struct BurnInput {
     pub amount: SmartI128,
-    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=NUMBER_OF_ADDRESSES - 1))]
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
     pub from_account_index: usize,
     #[arbitrary(with = |u: &mut Unstructured| {
         // biased bool - only sometimes decline the auth
-        Ok(<[bool; NUMBER_OF_ADDRESSES]>::try_from(
+        Ok(<[bool; MAX_NUMBER_OF_ADDRESSES]>::try_from(
             std::iter::from_fn(|| Some(u.ratio(9, 10).unwrap_or(true)))
-                .take(NUMBER_OF_ADDRESSES)
+                .take(MAX_NUMBER_OF_ADDRESSES)
                 .collect::<Vec<_>>()
         ).unwrap())
     })]
-    pub auths: [bool; NUMBER_OF_ADDRESSES],
+    pub auths: [bool; MAX_NUMBER_OF_ADDRESSES],
+    #[arbitrary(with = |u: &mut Unstructured| {
+        // only sometimes corrupt the auths
+        if u.ratio(1, 10)? { Ok(Some(u.arbitrary()?)) } else { Ok(None) }
+    })]
+    pub auth_corruption: Option<AuthCorruption>,
+    #[arbitrary(with = |u: &mut Unstructured| {
+        // only sometimes sign the wrong invocation
+        if u.ratio(1, 10)? { Ok(Some(u.arbitrary()?)) } else { Ok(None) }
+    })]
+    pub invocation_mismatch: Option<InvocationMismatch>,
+}
+
+#[derive(Clone, Debug, arbitrary::Arbitrary)]
+pub struct BalanceInput {
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
+    pub id_account_index: usize,
+}
+
+#[derive(Clone, Debug, arbitrary::Arbitrary)]
+pub struct AllowanceInput {
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
+    pub from_account_index: usize,
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
+    pub spender_account_index: usize,
+}
+
+#[derive(Clone, Debug, arbitrary::Arbitrary)]
+pub struct SetAdminInput {
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
+    pub new_admin_account_index: usize,
+    #[arbitrary(with = |u: &mut Unstructured| {
+        // biased bool - only sometimes decline the auth
+        Ok(<[bool; MAX_NUMBER_OF_ADDRESSES]>::try_from(
+            std::iter::from_fn(|| Some(u.ratio(9, 10).unwrap_or(true)))
+                .take(MAX_NUMBER_OF_ADDRESSES)
+                .collect::<Vec<_>>()
+        ).unwrap())
+    })]
+    pub auths: [bool; MAX_NUMBER_OF_ADDRESSES],
+}
+
+#[derive(Clone, Debug, arbitrary::Arbitrary)]
+pub struct ClawbackInput {
+    pub amount: SmartI128,
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
+    pub from_account_index: usize,
+    #[arbitrary(with = |u: &mut Unstructured| {
+        // biased bool - only sometimes decline the auth
+        Ok(<[bool; MAX_NUMBER_OF_ADDRESSES]>::try_from(
+            std::iter::from_fn(|| Some(u.ratio(9, 10).unwrap_or(true)))
+                .take(MAX_NUMBER_OF_ADDRESSES)
+                .collect::<Vec<_>>()
+        ).unwrap())
+    })]
+    pub auths: [bool; MAX_NUMBER_OF_ADDRESSES],
+}
+
+#[derive(Clone, Debug, arbitrary::Arbitrary)]
+pub struct SetAuthorizedInput {
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
+    pub id_account_index: usize,
+    pub authorize: bool,
+    #[arbitrary(with = |u: &mut Unstructured| {
+        // biased bool - only sometimes decline the auth
+        Ok(<[bool; MAX_NUMBER_OF_ADDRESSES]>::try_from(
+            std::iter::from_fn(|| Some(u.ratio(9, 10).unwrap_or(true)))
+                .take(MAX_NUMBER_OF_ADDRESSES)
+                .collect::<Vec<_>>()
+        ).unwrap())
+    })]
+    pub auths: [bool; MAX_NUMBER_OF_ADDRESSES],
+}
+
+#[derive(Clone, Debug, arbitrary::Arbitrary)]
+pub struct AuthorizedInput {
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
+    pub id_account_index: usize,
+}
+
+/// Call the token's initialization function again,
+/// with every auth mocked, which must fail.
+#[derive(Clone, Debug, arbitrary::Arbitrary)]
+pub struct ReinitializeInput {
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
+    pub admin_account_index: usize,
+    pub params: InitParams,
+}
+
+/// Re-submit the auth entries of a previously successful call,
+/// repeating the same call.
+#[derive(Clone, Debug, arbitrary::Arbitrary)]
+pub struct ReplayInput {
+    /// Index into the successful calls so far, wrapped to their number.
+    pub call_index: usize,
 }
 
 #[derive(Clone, Debug, arbitrary::Arbitrary)]
 pub struct ApproveAndTransferFromInput {
     pub amount: SmartI128,
     pub expiration_ledger: u32,
-    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=NUMBER_OF_ADDRESSES - 1))]
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
     pub from_account_index: usize,
-    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=NUMBER_OF_ADDRESSES - 1))]
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
     pub spender_account_index: usize,
-    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=NUMBER_OF_ADDRESSES - 1))]
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
     pub to_account_index: usize,
     #[arbitrary(with = |u: &mut Unstructured| {
         // biased bool - only sometimes decline the auth
-        Ok(<[bool; NUMBER_OF_ADDRESSES]>::try_from(
+        Ok(<[bool; MAX_NUMBER_OF_ADDRESSES]>::try_from(
             std::iter::from_fn(|| Some(u.ratio(9, 10).unwrap_or(true)))
-                .take(NUMBER_OF_ADDRESSES)
+                .take(MAX_NUMBER_OF_ADDRESSES)
                 .collect::<Vec<_>>()
         ).unwrap())
     })]
-    pub auths: [bool; NUMBER_OF_ADDRESSES],
+    pub auths: [bool; MAX_NUMBER_OF_ADDRESSES],
 }
 
 #[derive(Clone, Debug, arbitrary::Arbitrary)]
 pub struct ApproveAndBurnFromInput {
     pub amount: SmartI128,
     pub expiration_ledger: u32,
-    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=NUMBER_OF_ADDRESSES - 1))]
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
     pub from_account_index: usize,
-    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=NUMBER_OF_ADDRESSES - 1))]
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
     pub spender_account_index: usize,
-    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=NUMBER_OF_ADDRESSES - 1))]
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(0..=MAX_NUMBER_OF_ADDRESSES - 1))]
     pub to_account_index: usize,
     #[arbitrary(with = |u: &mut Unstructured| {
         // biased bool - only sometimes decline the auth
-        Ok(<[bool; NUMBER_OF_ADDRESSES]>::try_from(
+        Ok(<[bool; MAX_NUMBER_OF_ADDRESSES]>::try_from(
             std::iter::from_fn(|| Some(u.ratio(9, 10).unwrap_or(true)))
-                .take(NUMBER_OF_ADDRESSES)
+                .take(MAX_NUMBER_OF_ADDRESSES)
                 .collect::<Vec<_>>()
         ).unwrap())
     })]
-    pub auths: [bool; NUMBER_OF_ADDRESSES],
+    pub auths: [bool; MAX_NUMBER_OF_ADDRESSES],
 }
 
 impl ApproveAndTransferFromInput {
@@ -194,6 +454,8 @@ impl ApproveAndTransferFromInput {
             from_account_index: self.from_account_index,
             spender_account_index: self.spender_account_index,
             auths: self.auths,
+            auth_corruption: None,
+            invocation_mismatch: None,
         }
     }
 
@@ -204,6 +466,8 @@ impl ApproveAndTransferFromInput {
             from_account_index: self.from_account_index,
             to_account_index: self.to_account_index,
             auths: self.auths,
+            auth_corruption: None,
+            invocation_mismatch: None,
         }
     }
 }
@@ -216,6 +480,8 @@ impl ApproveAndBurnFromInput {
             from_account_index: self.from_account_index,
             spender_account_index: self.spender_account_index,
             auths: self.auths,
+            auth_corruption: None,
+            invocation_mismatch: None,
         }
     }
 
@@ -225,6 +491,91 @@ impl ApproveAndBurnFromInput {
             spender_account_index: self.spender_account_index,
             from_account_index: self.from_account_index,
             auths: self.auths,
+            auth_corruption: None,
+            invocation_mismatch: None,
+        }
+    }
+}
+
+impl Input {
+    /// Use `number_of_addresses` addresses for this run,
+    /// wrapping every command's account indexes to fit.
+    pub fn set_number_of_addresses(&mut self, number_of_addresses: usize) {
+        assert!((MIN_NUMBER_OF_ADDRESSES..=MAX_NUMBER_OF_ADDRESSES).contains(&number_of_addresses));
+        self.address_generator.number_of_addresses = number_of_addresses;
+        self.init.admin_account_index %= number_of_addresses;
+        for transaction in &mut self.transactions {
+            for command in &mut transaction.commands {
+                command.wrap_account_indexes(number_of_addresses);
+            }
+        }
+    }
+}
+
+impl Command {
+    fn wrap_account_indexes(&mut self, number_of_addresses: usize) {
+        let wrap = |index: &mut usize| *index %= number_of_addresses;
+        match self {
+            Command::Mint(input) => {
+                wrap(&mut input.to_account_index);
+            }
+            Command::Approve(input) => {
+                wrap(&mut input.from_account_index);
+                wrap(&mut input.spender_account_index);
+            }
+            Command::TransferFrom(input) => {
+                wrap(&mut input.spender_account_index);
+                wrap(&mut input.from_account_index);
+                wrap(&mut input.to_account_index);
+            }
+            Command::Transfer(input) => {
+                wrap(&mut input.from_account_index);
+                wrap(&mut input.to_account_index);
+            }
+            Command::BurnFrom(input) => {
+                wrap(&mut input.spender_account_index);
+                wrap(&mut input.from_account_index);
+            }
+            Command::Burn(input) => {
+                wrap(&mut input.from_account_index);
+            }
+            Command::Balance(input) => {
+                wrap(&mut input.id_account_index);
+            }
+            Command::Allowance(input) => {
+                wrap(&mut input.from_account_index);
+                wrap(&mut input.spender_account_index);
+            }
+            Command::Decimals
+            | Command::Name
+            | Command::Symbol
+            | Command::Replay(_)
+            | Command::Upgrade => {}
+            Command::SetAdmin(input) => {
+                wrap(&mut input.new_admin_account_index);
+            }
+            Command::Clawback(input) => {
+                wrap(&mut input.from_account_index);
+            }
+            Command::SetAuthorized(input) => {
+                wrap(&mut input.id_account_index);
+            }
+            Command::Authorized(input) => {
+                wrap(&mut input.id_account_index);
+            }
+            Command::Reinitialize(input) => {
+                wrap(&mut input.admin_account_index);
+            }
+            Command::ApproveAndTransferFrom(input) => {
+                wrap(&mut input.from_account_index);
+                wrap(&mut input.spender_account_index);
+                wrap(&mut input.to_account_index);
+            }
+            Command::ApproveAndBurnFrom(input) => {
+                wrap(&mut input.from_account_index);
+                wrap(&mut input.spender_account_index);
+                wrap(&mut input.to_account_index);
+            }
         }
     }
 }
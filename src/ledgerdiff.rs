@@ -0,0 +1,111 @@
+use soroban_sdk::testutils::Snapshot;
+use soroban_sdk::xdr::{ContractDataDurability, LedgerEntryData, LedgerKey, ScAddress, ScVal};
+use std::collections::BTreeMap;
+use std::vec::Vec as RustVec;
+
+/// A difference in a single ledger entry between two snapshots.
+#[derive(Clone, Debug)]
+pub struct LedgerChange {
+    pub key: LedgerKey,
+    pub kind: ChangeKind,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ChangeKind {
+    Created,
+    Deleted,
+    /// The contents of the entry changed,
+    /// and maybe also its TTL.
+    Modified,
+    /// Only the TTL of the entry changed.
+    Ttl {
+        before: Option<u32>,
+        after: Option<u32>,
+    },
+}
+
+/// The type of contract storage an entry belongs to.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum StorageType {
+    Temporary,
+    Persistent,
+    /// The contract instance entry, which holds all instance storage.
+    Instance,
+}
+
+impl LedgerChange {
+    /// Whether this is a change to the storage of `contract`,
+    /// including its instance storage.
+    pub fn is_contract_data_of(&self, contract: &ScAddress) -> bool {
+        match &self.key {
+            LedgerKey::ContractData(data) => data.contract == *contract,
+            _ => false,
+        }
+    }
+
+    /// Whether this is a change to a Stellar account or one of its trustlines.
+    pub fn is_account_entry(&self) -> bool {
+        matches!(self.key, LedgerKey::Account(_) | LedgerKey::Trustline(_))
+    }
+
+    /// The type of storage changed, if this is a change to contract data.
+    pub fn storage_type(&self) -> Option<StorageType> {
+        match &self.key {
+            LedgerKey::ContractData(data) => match (&data.key, data.durability) {
+                (ScVal::LedgerKeyContractInstance, _) => Some(StorageType::Instance),
+                (_, ContractDataDurability::Temporary) => Some(StorageType::Temporary),
+                (_, ContractDataDurability::Persistent) => Some(StorageType::Persistent),
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Find every ledger entry that was created, deleted,
+/// or had its contents or TTL changed between two snapshots.
+///
+/// Changes are ordered by key.
+pub fn diff_ledger(pre_snapshot: &Snapshot, post_snapshot: &Snapshot) -> RustVec<LedgerChange> {
+    let get_entries = |snapshot: &Snapshot| -> BTreeMap<LedgerKey, (LedgerEntryData, Option<u32>)> {
+        snapshot
+            .ledger
+            .ledger_entries
+            .iter()
+            .map(|(key, (entry, ttl))| ((**key).clone(), (entry.data.clone(), *ttl)))
+            .collect()
+    };
+
+    let pre_entries = get_entries(pre_snapshot);
+    let post_entries = get_entries(post_snapshot);
+
+    let mut changes = RustVec::new();
+
+    for (key, (pre_data, pre_ttl)) in &pre_entries {
+        let kind = match post_entries.get(key) {
+            None => ChangeKind::Deleted,
+            Some((post_data, _)) if post_data != pre_data => ChangeKind::Modified,
+            Some((_, post_ttl)) if post_ttl != pre_ttl => ChangeKind::Ttl {
+                before: *pre_ttl,
+                after: *post_ttl,
+            },
+            Some(_) => continue,
+        };
+        changes.push(LedgerChange {
+            key: key.clone(),
+            kind,
+        });
+    }
+
+    for key in post_entries.keys() {
+        if !pre_entries.contains_key(key) {
+            changes.push(LedgerChange {
+                key: key.clone(),
+                kind: ChangeKind::Created,
+            });
+        }
+    }
+
+    changes.sort_by(|a, b| a.key.cmp(&b.key));
+
+    changes
+}
@@ -1,12 +1,16 @@
+pub mod accounts;
 pub mod addrgen;
 pub mod config;
 pub mod fuzz;
 pub mod input;
+pub mod ledgerdiff;
+pub mod spec;
 pub mod util;
 
 pub use config::{Config, ContractTokenOps, TokenAdminClient};
-pub use fuzz::fuzz_token;
-pub use input::Input;
+pub use fuzz::{fuzz_token, AddressResult, BoolResult, UnitResult};
+pub use input::{InitParams, Input};
+pub use spec::TokenDeclaration;
 
 // copied from somewhere in the sdk
 const DAY_IN_LEDGERS: u32 = 17280;
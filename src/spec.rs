@@ -0,0 +1,675 @@
+//! Fuzzing a token from its compiled WASM alone.
+//!
+//! A [`TokenDeclaration`] says where the token's WASM is,
+//! and how to call its initialization and mint functions
+//! with the values the fuzzer supplies, e.g.
+//!
+//! ```text
+//! initialize(admin, decimals, name, symbol)
+//! mint(to, amount)
+//! ```
+//!
+//! The declaration is checked against the contract spec embedded in the WASM,
+//! which also tells which of the Stellar Asset Contract's
+//! optional admin functions the token has.
+
+use crate::config::{
+    self, ContractTokenOps, StellarAssetAdminClient, SupportedAdminFunctions, TokenAdminClient,
+};
+use crate::fuzz::{AddressResult, BoolResult, UnitResult};
+use crate::input::InitParams;
+use soroban_sdk::token::StellarAssetClient;
+use soroban_sdk::xdr::{ScSpecEntry, ScSpecFunctionV0, ScSpecTypeDef, SorobanAuthorizationEntry};
+use soroban_sdk::{Address, Env, Error, IntoVal, String, Symbol, Val};
+use std::path::Path;
+use std::sync::Arc;
+use std::vec::Vec as RustVec;
+
+/// The initialization function of the example token,
+/// used if none is declared.
+pub const DEFAULT_INIT: &str = "initialize(admin, decimals, name, symbol)";
+/// The mint function of the `StellarAssetInterface`,
+/// used if none is declared.
+pub const DEFAULT_MINT: &str = "mint(to, amount)";
+
+/// The functions of `TokenInterface` the fuzzer calls directly.
+const TOKEN_INTERFACE_FUNCTIONS: &[&str] = &[
+    "allowance",
+    "approve",
+    "balance",
+    "transfer",
+    "transfer_from",
+    "burn",
+    "burn_from",
+    "decimals",
+    "name",
+    "symbol",
+];
+
+/// A token compiled to WASM, and how to initialize it and mint it.
+#[derive(Clone, Debug)]
+pub struct TokenDeclaration {
+    wasm: Arc<[u8]>,
+    init: FunctionDeclaration,
+    mint: FunctionDeclaration,
+    supported: SupportedAdminFunctions,
+}
+
+/// A call to one of the token's functions,
+/// with the arguments in the order the function takes them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FunctionDeclaration {
+    pub name: std::string::String,
+    pub args: RustVec<Arg>,
+}
+
+/// An argument supplied by the fuzzer.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Arg {
+    /// The admin, an `Address`, for the initialization function.
+    Admin,
+    /// The fuzzed decimals, a `u32`, for the initialization function.
+    Decimals,
+    /// The fuzzed name, a `String`, for the initialization function.
+    Name,
+    /// The fuzzed symbol, a `String`, for the initialization function.
+    Symbol,
+    /// The recipient, an `Address`, for the mint function.
+    To,
+    /// The amount, an `i128`, for the mint function.
+    Amount,
+}
+
+impl TokenDeclaration {
+    /// Read the token's WASM from `wasm_path`,
+    /// and check the declarations of its initialization and mint functions,
+    /// e.g. [`DEFAULT_INIT`] and [`DEFAULT_MINT`], against its contract spec.
+    ///
+    /// This panics if the WASM can't be read,
+    /// or doesn't match the declarations.
+    pub fn new(wasm_path: &Path, init: &str, mint: &str) -> TokenDeclaration {
+        let wasm = std::fs::read(wasm_path)
+            .unwrap_or_else(|e| panic!("failed to read token WASM {}: {e}", wasm_path.display()));
+        let spec = soroban_spec::read::from_wasm(&wasm).unwrap_or_else(|e| {
+            panic!(
+                "failed to read the contract spec of {}: {e}",
+                wasm_path.display()
+            )
+        });
+        let functions: RustVec<ScSpecFunctionV0> = spec
+            .into_iter()
+            .filter_map(|entry| match entry {
+                ScSpecEntry::FunctionV0(function) => Some(function),
+                _ => None,
+            })
+            .collect();
+
+        let missing: RustVec<&str> = TOKEN_INTERFACE_FUNCTIONS
+            .iter()
+            .copied()
+            .filter(|name| find_function(&functions, name).is_none())
+            .collect();
+        assert!(
+            missing.is_empty(),
+            "the token doesn't implement `TokenInterface`, missing {missing:?}"
+        );
+
+        let init = FunctionDeclaration::parse(init);
+        init.check(
+            &functions,
+            &[Arg::Admin, Arg::Decimals, Arg::Name, Arg::Symbol],
+            &[Arg::Admin],
+        );
+        let mint = FunctionDeclaration::parse(mint);
+        mint.check(&functions, &[Arg::To, Arg::Amount], &[Arg::To, Arg::Amount]);
+
+        TokenDeclaration {
+            wasm: wasm.into(),
+            init,
+            mint,
+            supported: SupportedAdminFunctions::from_spec(&functions),
+        }
+    }
+
+    /// Read the declaration from the file named by
+    /// the `TOKEN_DECLARATION` environment variable, if set,
+    /// otherwise from the `TOKEN_WASM`, `TOKEN_INIT` and `TOKEN_MINT`
+    /// environment variables.
+    ///
+    /// `TOKEN_WASM` is required,
+    /// and the others default to [`DEFAULT_INIT`] and [`DEFAULT_MINT`].
+    pub fn from_env() -> TokenDeclaration {
+        if let Some(path) = std::env::var_os("TOKEN_DECLARATION") {
+            return TokenDeclaration::from_file(Path::new(&path));
+        }
+
+        let wasm = std::env::var_os("TOKEN_WASM").expect(
+            "set TOKEN_WASM to the path of the token's WASM, \
+             or TOKEN_DECLARATION to the path of a declaration file",
+        );
+        let init = std::env::var("TOKEN_INIT").unwrap_or_else(|_| DEFAULT_INIT.to_string());
+        let mint = std::env::var("TOKEN_MINT").unwrap_or_else(|_| DEFAULT_MINT.to_string());
+
+        TokenDeclaration::new(Path::new(&wasm), &init, &mint)
+    }
+
+    /// Read the declaration from a file of `key = value` lines,
+    /// with the same keys as the environment variables of
+    /// [`TokenDeclaration::from_env`], in lowercase, e.g.
+    ///
+    /// ```text
+    /// # relative to this file
+    /// wasm = target/wasm32-unknown-unknown/release/my_token.wasm
+    /// init = initialize(admin, decimals, name, symbol)
+    /// mint = mint(to, amount)
+    /// ```
+    pub fn from_file(path: &Path) -> TokenDeclaration {
+        let contents = std::fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("failed to read token declaration {}: {e}", path.display()));
+
+        let mut wasm = None;
+        let mut init = DEFAULT_INIT.to_string();
+        let mut mint = DEFAULT_MINT.to_string();
+
+        for line in contents.lines() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                panic!("expected `key = value` in token declaration, found `{line}`");
+            };
+            let value = value.trim();
+            match key.trim() {
+                "wasm" => wasm = Some(path.parent().unwrap_or(Path::new(".")).join(value)),
+                "init" => init = value.to_string(),
+                "mint" => mint = value.to_string(),
+                key => panic!("unknown key `{key}` in token declaration"),
+            }
+        }
+
+        let wasm = wasm.unwrap_or_else(|| panic!("no `wasm` in {}", path.display()));
+        TokenDeclaration::new(&wasm, &init, &mint)
+    }
+
+    fn try_initialize(
+        &self,
+        env: &Env,
+        token_contract_id: &Address,
+        admin: &Address,
+        params: &InitParams,
+    ) -> UnitResult {
+        self.init
+            .try_invoke(env, token_contract_id, |arg| match arg {
+                Arg::Admin => admin.into_val(env),
+                Arg::Decimals => params.decimals.into_val(env),
+                Arg::Name => String::from_str(env, &params.name).into_val(env),
+                Arg::Symbol => String::from_str(env, &params.symbol).into_val(env),
+                Arg::To | Arg::Amount => unreachable!("checked against the spec"),
+            })
+    }
+}
+
+impl FunctionDeclaration {
+    /// Parse a declaration like `mint(to, amount)`.
+    pub fn parse(declaration: &str) -> FunctionDeclaration {
+        let declaration = declaration.trim();
+        let parsed = declaration
+            .strip_suffix(')')
+            .and_then(|d| d.split_once('('));
+        let Some((name, args)) = parsed else {
+            panic!("expected a declaration like `name(arg, ...)`, found `{declaration}`");
+        };
+
+        let args = args
+            .split(',')
+            .map(str::trim)
+            .filter(|arg| !arg.is_empty())
+            .map(Arg::parse)
+            .collect();
+
+        FunctionDeclaration {
+            name: name.trim().to_string(),
+            args,
+        }
+    }
+
+    /// Check that the function exists, and takes exactly the declared arguments,
+    /// each of which must be one of `allowed`, and all of `required`.
+    fn check(&self, functions: &[ScSpecFunctionV0], allowed: &[Arg], required: &[Arg]) {
+        let name = &self.name;
+        let Some(function) = find_function(functions, name) else {
+            panic!("the token has no function `{name}`");
+        };
+
+        for arg in &self.args {
+            assert!(
+                allowed.contains(arg),
+                "`{name}` can't take `{}`, only {:?}",
+                arg.as_str(),
+                allowed.iter().map(|a| a.as_str()).collect::<RustVec<_>>(),
+            );
+            assert_eq!(
+                self.args.iter().filter(|a| *a == arg).count(),
+                1,
+                "`{name}` takes `{}` more than once",
+                arg.as_str(),
+            );
+        }
+        for arg in required {
+            assert!(
+                self.args.contains(arg),
+                "`{name}` must take `{}`",
+                arg.as_str(),
+            );
+        }
+
+        assert_eq!(
+            function.inputs.len(),
+            self.args.len(),
+            "`{name}` is declared with {} arguments, but takes {}",
+            self.args.len(),
+            function.inputs.len(),
+        );
+        for (arg, input) in self.args.iter().zip(function.inputs.iter()) {
+            assert_eq!(
+                input.type_,
+                arg.spec_type(),
+                "`{}` is passed as `{name}`'s `{}` argument, which has the wrong type",
+                arg.as_str(),
+                input.name.to_utf8_string_lossy(),
+            );
+        }
+    }
+
+    fn args(&self, env: &Env, arg_val: impl Fn(Arg) -> Val) -> soroban_sdk::Vec<Val> {
+        let mut args = soroban_sdk::Vec::new(env);
+        for arg in &self.args {
+            args.push_back(arg_val(*arg));
+        }
+        args
+    }
+
+    fn try_invoke(
+        &self,
+        env: &Env,
+        contract_id: &Address,
+        arg_val: impl Fn(Arg) -> Val,
+    ) -> UnitResult {
+        let args = self.args(env, arg_val);
+        let r =
+            env.try_invoke_contract::<Val, Error>(contract_id, &Symbol::new(env, &self.name), args);
+
+        // Only whether the call succeeded matters, not what it returned.
+        r.map(|r| r.map(|_| ()))
+    }
+}
+
+impl Arg {
+    fn parse(arg: &str) -> Arg {
+        match arg {
+            "admin" => Arg::Admin,
+            "decimals" => Arg::Decimals,
+            "name" => Arg::Name,
+            "symbol" => Arg::Symbol,
+            "to" => Arg::To,
+            "amount" => Arg::Amount,
+            arg => panic!(
+                "unknown argument `{arg}`, \
+                 expected one of admin, decimals, name, symbol, to or amount"
+            ),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Arg::Admin => "admin",
+            Arg::Decimals => "decimals",
+            Arg::Name => "name",
+            Arg::Symbol => "symbol",
+            Arg::To => "to",
+            Arg::Amount => "amount",
+        }
+    }
+
+    fn spec_type(&self) -> ScSpecTypeDef {
+        match self {
+            Arg::Admin | Arg::To => ScSpecTypeDef::Address,
+            Arg::Decimals => ScSpecTypeDef::U32,
+            Arg::Name | Arg::Symbol => ScSpecTypeDef::String,
+            Arg::Amount => ScSpecTypeDef::I128,
+        }
+    }
+}
+
+impl SupportedAdminFunctions {
+    /// Find the optional admin functions in the contract spec,
+    /// with the arguments and results of the `StellarAssetInterface`.
+    fn from_spec(functions: &[ScSpecFunctionV0]) -> SupportedAdminFunctions {
+        use ScSpecTypeDef::{Address, Bool, I128};
+
+        let has = |name: &str, inputs: &[ScSpecTypeDef], output: Option<ScSpecTypeDef>| {
+            let Some(function) = find_function(functions, name) else {
+                return false;
+            };
+            let returns = |type_: &ScSpecTypeDef| match function.outputs.first() {
+                Some(ScSpecTypeDef::Result(result)) => *result.ok_type == *type_,
+                Some(output) => output == type_,
+                None => false,
+            };
+            function.inputs.iter().map(|i| &i.type_).eq(inputs)
+                && output.as_ref().is_none_or(returns)
+        };
+
+        SupportedAdminFunctions {
+            set_admin: has("set_admin", &[Address], None),
+            clawback: has("clawback", &[Address, I128], None),
+            set_authorized: has("set_authorized", &[Address, Bool], None),
+            authorized: has("authorized", &[Address], Some(Bool)),
+            admin: has("admin", &[], Some(Address)),
+        }
+    }
+}
+
+fn find_function<'a>(
+    functions: &'a [ScSpecFunctionV0],
+    name: &str,
+) -> Option<&'a ScSpecFunctionV0> {
+    functions
+        .iter()
+        .find(|function| function.name.0.to_utf8_string_lossy() == name)
+}
+
+/// [`ContractTokenOps`] for [`Config::declared`](crate::Config::declared).
+pub(crate) struct DeclaredTokenOps(pub(crate) TokenDeclaration);
+
+/// An admin client that mints through the declared mint function,
+/// and makes other admin calls through [`StellarAssetClient`].
+struct DeclaredAdminClient<'a> {
+    admin_client: StellarAssetAdminClient<'a>,
+    mint: FunctionDeclaration,
+    auths: Option<&'a [SorobanAuthorizationEntry]>,
+}
+
+impl ContractTokenOps for DeclaredTokenOps {
+    fn register_contract_init(
+        &self,
+        env: &Env,
+        admin: &Address,
+        params: &InitParams,
+    ) -> Option<Address> {
+        let token_contract_id = config::register_wasm(env, None, &self.0.wasm);
+        let r = self
+            .0
+            .try_initialize(env, &token_contract_id, admin, params);
+        matches!(r, Ok(Ok(()))).then_some(token_contract_id)
+    }
+
+    fn try_reinitialize(
+        &self,
+        env: &Env,
+        token_contract_id: &Address,
+        admin: &Address,
+        params: &InitParams,
+    ) -> Option<UnitResult> {
+        Some(self.0.try_initialize(env, token_contract_id, admin, params))
+    }
+
+    fn reregister_contract(&self, env: &Env, token_contract_id: &Address) {
+        config::register_wasm(env, Some(token_contract_id), &self.0.wasm);
+    }
+
+    fn new_admin_client<'a>(
+        &self,
+        env: &Env,
+        token_contract_id: &Address,
+    ) -> Box<dyn TokenAdminClient<'a> + 'a> {
+        Box::new(DeclaredAdminClient {
+            admin_client: StellarAssetAdminClient {
+                admin_client: StellarAssetClient::new(env, token_contract_id),
+                supported: self.0.supported,
+            },
+            mint: self.0.mint.clone(),
+            auths: None,
+        })
+    }
+}
+
+/// The value of each argument of the declared mint function.
+fn mint_arg<'b>(env: &'b Env, to: &'b Address, amount: &'b i128) -> impl Fn(Arg) -> Val + 'b {
+    move |arg| match arg {
+        Arg::To => to.into_val(env),
+        Arg::Amount => amount.into_val(env),
+        Arg::Admin | Arg::Decimals | Arg::Name | Arg::Symbol => {
+            unreachable!("checked against the spec")
+        }
+    }
+}
+
+impl<'a> TokenAdminClient<'a> for DeclaredAdminClient<'a> {
+    fn try_mint(&self, to: &Address, amount: &i128) -> UnitResult {
+        let client = &self.admin_client.admin_client;
+        let env = &client.env;
+
+        // Like the `set_auths` method of generated contract clients.
+        let prev_auth_manager = env.host().snapshot_auth_manager().unwrap();
+        if let Some(auths) = self.auths {
+            env.set_auths(auths);
+        }
+        let r = self
+            .mint
+            .try_invoke(env, &client.address, mint_arg(env, to, amount));
+        env.host().set_auth_manager(prev_auth_manager).unwrap();
+
+        r
+    }
+
+    fn mint_invocation(
+        &self,
+        env: &Env,
+        to: &Address,
+        amount: &i128,
+    ) -> (&str, soroban_sdk::Vec<Val>) {
+        (
+            &self.mint.name,
+            self.mint.args(env, mint_arg(env, to, amount)),
+        )
+    }
+
+    fn try_set_admin(&self, new_admin: &Address) -> Option<UnitResult> {
+        self.admin_client.try_set_admin(new_admin)
+    }
+
+    fn try_clawback(&self, from: &Address, amount: &i128) -> Option<UnitResult> {
+        self.admin_client.try_clawback(from, amount)
+    }
+
+    fn try_set_authorized(&self, id: &Address, authorize: &bool) -> Option<UnitResult> {
+        self.admin_client.try_set_authorized(id, authorize)
+    }
+
+    fn try_authorized(&self, id: &Address) -> Option<BoolResult> {
+        self.admin_client.try_authorized(id)
+    }
+
+    fn try_admin(&self) -> Option<AddressResult> {
+        self.admin_client.try_admin()
+    }
+
+    fn set_auths<'b>(
+        &self,
+        auths: &'b [SorobanAuthorizationEntry],
+    ) -> Box<dyn TokenAdminClient<'b> + 'b> {
+        let client = &self.admin_client.admin_client;
+        Box::new(DeclaredAdminClient {
+            admin_client: StellarAssetAdminClient {
+                admin_client: StellarAssetClient::new(&client.env, &client.address)
+                    .set_auths(auths),
+                supported: self.admin_client.supported,
+            },
+            mint: self.mint.clone(),
+            auths: Some(auths),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::xdr::{Limits, ScSpecFunctionInputV0, WriteXdr};
+
+    fn function(name: &str, inputs: &[(&str, ScSpecTypeDef)]) -> ScSpecFunctionV0 {
+        ScSpecFunctionV0 {
+            doc: Default::default(),
+            name: name.try_into().unwrap(),
+            inputs: inputs
+                .iter()
+                .map(|(name, type_)| ScSpecFunctionInputV0 {
+                    doc: Default::default(),
+                    name: (*name).try_into().unwrap(),
+                    type_: type_.clone(),
+                })
+                .collect::<RustVec<_>>()
+                .try_into()
+                .unwrap(),
+            outputs: Default::default(),
+        }
+    }
+
+    /// The functions of the example token, whose `TokenInterface`
+    /// functions take no arguments, since only their names are checked.
+    fn token_functions() -> RustVec<ScSpecFunctionV0> {
+        use ScSpecTypeDef::{Address, String, I128, U32};
+
+        let mut functions: RustVec<_> = TOKEN_INTERFACE_FUNCTIONS
+            .iter()
+            .map(|name| function(name, &[]))
+            .collect();
+        functions.push(function(
+            "initialize",
+            &[
+                ("admin", Address),
+                ("decimal", U32),
+                ("name", String),
+                ("symbol", String),
+            ],
+        ));
+        functions.push(function("mint", &[("to", Address), ("amount", I128)]));
+        functions
+    }
+
+    /// A WASM module with nothing but a contract spec.
+    fn wasm(functions: &[ScSpecFunctionV0]) -> RustVec<u8> {
+        let name = b"contractspecv0";
+        let mut section = vec![name.len() as u8];
+        section.extend_from_slice(name);
+        for function in functions {
+            let entry = ScSpecEntry::FunctionV0(function.clone());
+            section.extend(entry.to_xdr(Limits::none()).unwrap());
+        }
+
+        let mut wasm = b"\0asm\x01\0\0\0".to_vec();
+        // A custom section, with its LEB128 length.
+        wasm.push(0);
+        let mut len = section.len();
+        loop {
+            let byte = (len & 0x7f) as u8;
+            len >>= 7;
+            if len == 0 {
+                wasm.push(byte);
+                break;
+            }
+            wasm.push(byte | 0x80);
+        }
+        wasm.extend(section);
+        wasm
+    }
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "soroban-token-fuzzer-{name}-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn valid_declaration() {
+        let mint = FunctionDeclaration::parse(" mint( to ,amount ) ");
+        assert_eq!(mint.name, "mint");
+        assert_eq!(mint.args, [Arg::To, Arg::Amount]);
+        mint.check(
+            &token_functions(),
+            &[Arg::To, Arg::Amount],
+            &[Arg::To, Arg::Amount],
+        );
+
+        let init = FunctionDeclaration::parse(DEFAULT_INIT);
+        assert_eq!(init.name, "initialize");
+        assert_eq!(
+            init.args,
+            [Arg::Admin, Arg::Decimals, Arg::Name, Arg::Symbol]
+        );
+        init.check(
+            &token_functions(),
+            &[Arg::Admin, Arg::Decimals, Arg::Name, Arg::Symbol],
+            &[Arg::Admin],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown argument `owner`")]
+    fn unknown_argument() {
+        FunctionDeclaration::parse("mint(owner, amount)");
+    }
+
+    #[test]
+    #[should_panic(expected = "`mint` takes `to` more than once")]
+    fn duplicate_argument() {
+        FunctionDeclaration::parse("mint(to, to)").check(
+            &token_functions(),
+            &[Arg::To, Arg::Amount],
+            &[Arg::To],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "`initialize` is declared with 3 arguments, but takes 4")]
+    fn wrong_arity() {
+        FunctionDeclaration::parse("initialize(admin, decimals, name)").check(
+            &token_functions(),
+            &[Arg::Admin, Arg::Decimals, Arg::Name, Arg::Symbol],
+            &[Arg::Admin],
+        );
+    }
+
+    #[test]
+    fn declaration_file_skips_comments_and_blank_lines() {
+        let dir = temp_dir("declaration-file");
+        std::fs::write(dir.join("token.wasm"), wasm(&token_functions())).unwrap();
+        let path = dir.join("token.decl");
+        std::fs::write(
+            &path,
+            "# the example token\n\
+             \n\
+             wasm = token.wasm  # relative to this file\n\
+             \x20  \n\
+             init = initialize(admin, decimals, name, symbol)\n\
+             # mint = mint(amount, to)\n\
+             mint=mint(to, amount)\n",
+        )
+        .unwrap();
+
+        let declaration = TokenDeclaration::from_file(&path);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(declaration.init.name, "initialize");
+        assert_eq!(
+            declaration.init.args,
+            [Arg::Admin, Arg::Decimals, Arg::Name, Arg::Symbol]
+        );
+        assert_eq!(declaration.mint.name, "mint");
+        assert_eq!(declaration.mint.args, [Arg::To, Arg::Amount]);
+    }
+}
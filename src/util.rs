@@ -22,17 +22,32 @@ pub struct SmartI128(pub i128);
 impl<'a> Arbitrary<'a> for SmartI128 {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
         const SMART_CHANCE: (u8, u8) = (1, 100);
-        const SMART_VALS: &[i128] = &[0, -1, 1, i128::MIN, i128::MAX];
+        const SMART_VALS: &[i128] = &[
+            0,
+            -1,
+            1,
+            i128::MIN,
+            i128::MAX,
+            i64::MAX as i128,
+            i64::MAX as i128 + 1,
+        ];
+        // Some tokens, like the Stellar Asset Contract,
+        // store some balances as `i64`.
+        const NEAR_I64_MAX_CHANCE: (u8, u8) = (1, 100);
+        const NEAR_I64_MAX_RANGE: i128 = 1000;
 
         if u.ratio(SMART_CHANCE.0, SMART_CHANCE.1)? {
             Ok(SmartI128(*u.choose(SMART_VALS)?))
+        } else if u.ratio(NEAR_I64_MAX_CHANCE.0, NEAR_I64_MAX_CHANCE.1)? {
+            let offset = u.int_in_range(-NEAR_I64_MAX_RANGE..=NEAR_I64_MAX_RANGE)?;
+            Ok(SmartI128(i64::MAX as i128 + offset))
         } else {
             Ok(SmartI128(u.arbitrary()?))
         }
     }
 
     fn size_hint(_depth: usize) -> (usize, Option<usize>) {
-        let size_u8 = core::mem::size_of::<u8>(); // for the ratio
+        let size_u8 = core::mem::size_of::<u8>() * 2; // for the ratios
         let size_usize = core::mem::size_of::<usize>(); // for the choose
         let size_i128 = core::mem::size_of::<i128>(); // for the arbitrary i128
         let needed = size_u8 + size_usize.min(size_i128);